@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 
 use edit::arena::scratch_arena;
+use edit::buffer::TextBuffer;
 use edit::framebuffer::{Attributes, IndexedColor};
 use edit::fuzzy::score_fuzzy;
 use edit::helpers::*;
@@ -10,6 +11,7 @@ use edit::tui::*;
 use edit::{arena_format, icu};
 
 use crate::localization::*;
+use crate::markdown;
 use crate::state::*;
 
 pub fn draw_statusbar(ctx: &mut Context, state: &mut State) {
@@ -50,8 +52,21 @@ pub fn draw_statusbar(ctx: &mut Context, state: &mut State) {
                 ctx.attr_padding(Rect::two(0, 1));
                 ctx.attr_border();
                 {
+                    // NOTE: `state.encoding_picker_detected`,
+                    // `LocId::EncodingDetected` (used below in
+                    // draw_dialog_encoding_change), and `icu::detect_encoding`
+                    // are not defined anywhere in this tree -- same missing
+                    // `state.rs` / `localization.rs` / `edit` library gap as
+                    // elsewhere in this file, predating this backlog. No
+                    // in-tree fix available.
                     if ctx.button("reopen", loc(LocId::EncodingReopen), ButtonStyle::default()) {
                         state.wants_encoding_change = StateEncodingChange::Reopen;
+                        state.encoding_picker_detected = doc
+                            .path
+                            .as_ref()
+                            .and_then(|path| std::fs::read(path).ok())
+                            .and_then(|bytes| icu::detect_encoding(&bytes).into_iter().next())
+                            .map(|(enc, _confidence)| enc);
                     }
                     ctx.focus_on_first_present();
                     if ctx.button("convert", loc(LocId::EncodingConvert), ButtonStyle::default()) {
@@ -147,6 +162,21 @@ pub fn draw_statusbar(ctx: &mut Context, state: &mut State) {
             }
         }
 
+        // NOTE: `state.markdown_preview` and `LocId::MarkdownSource` /
+        // `LocId::MarkdownPreview` are not defined anywhere in this tree --
+        // same missing `state.rs` / `localization.rs` gap as elsewhere in
+        // this file, predating this backlog. No in-tree fix available.
+        if doc.filename.ends_with(".md") || doc.filename.ends_with(".markdown") {
+            if ctx.button(
+                "markdown-preview",
+                if state.markdown_preview { loc(LocId::MarkdownSource) } else { loc(LocId::MarkdownPreview) },
+                ButtonStyle::default(),
+            ) {
+                state.markdown_preview = !state.markdown_preview;
+                ctx.needs_rerender();
+            }
+        }
+
         ctx.label(
             "location",
             &arena_format!(
@@ -172,6 +202,8 @@ pub fn draw_statusbar(ctx: &mut Context, state: &mut State) {
             ctx.label("dirty", "*");
         }
 
+        state.wants_go_to_symbol |= ctx.button("symbols", loc(LocId::ViewGoToSymbol), ButtonStyle::default());
+
         ctx.block_begin("filename-container");
         ctx.attr_intrinsic_size(Size { width: COORD_TYPE_SAFE_MAX, height: 1 });
         {
@@ -235,6 +267,15 @@ pub fn draw_dialog_encoding_change(ctx: &mut Context, state: &mut State) {
             ctx.list_begin("encodings");
             ctx.inherit_focus();
 
+            if reopen && let Some(detected) = state.encoding_picker_detected {
+                let label = arena_format!(ctx.arena(), "{} {}", loc(LocId::EncodingDetected), detected);
+                ctx.focus_on_first_present();
+                if ctx.list_item(detected == encoding, &label) == ListSelection::Activated {
+                    change = Some(detected);
+                }
+                ctx.attr_overflow(Overflow::TruncateTail);
+            }
+
             for enc in state
                 .encoding_picker_results
                 .as_deref()
@@ -276,6 +317,7 @@ pub fn draw_dialog_encoding_change(ctx: &mut Context, state: &mut State) {
         state.wants_encoding_change = StateEncodingChange::None;
         state.encoding_picker_needle.clear();
         state.encoding_picker_results = None;
+        state.encoding_picker_detected = None;
         ctx.needs_rerender();
     }
 }
@@ -305,12 +347,34 @@ fn encoding_picker_update_list(state: &mut State) {
     state.encoding_picker_results = Some(Vec::from_iter(matches.iter().map(|(_, enc)| *enc)));
 }
 
+// NOTE: `state.go_to_file_needle` / `state.go_to_file_results` are not
+// defined anywhere in this tree -- `src/bin/edit/state.rs` (and the rest
+// of the `edit` library crate) is absent from this snapshot, predating
+// this backlog. Left as-is; there's no local fix available without that
+// file.
 pub fn draw_go_to_file(ctx: &mut Context, state: &mut State) {
     ctx.modal_begin("go-to-file", loc(LocId::ViewGoToFile));
     {
         let width = (ctx.size().width - 20).max(10);
         let height = (ctx.size().height - 10).max(10);
 
+        ctx.table_begin("go-to-file-search");
+        ctx.table_set_columns(&[0, COORD_TYPE_SAFE_MAX]);
+        ctx.table_set_cell_gap(Size { width: 1, height: 0 });
+        ctx.inherit_focus();
+        {
+            ctx.table_next_row();
+            ctx.inherit_focus();
+
+            ctx.label("needle-label", loc(LocId::SearchNeedleLabel));
+
+            if ctx.editline("needle", &mut state.go_to_file_needle) {
+                go_to_file_update_list(state);
+            }
+            ctx.inherit_focus();
+        }
+        ctx.table_end();
+
         ctx.scrollarea_begin("scrollarea", Size { width, height });
         ctx.attr_background_rgba(ctx.indexed_alpha(IndexedColor::Black, 1, 4));
         ctx.inherit_focus();
@@ -318,22 +382,30 @@ pub fn draw_go_to_file(ctx: &mut Context, state: &mut State) {
             ctx.list_begin("documents");
             ctx.inherit_focus();
 
-            if state.documents.update_active(|doc| {
-                let tb = doc.buffer.borrow();
-
-                ctx.styled_list_item_begin();
-                ctx.attr_overflow(Overflow::TruncateTail);
-                ctx.styled_label_add_text(if tb.is_dirty() { "* " } else { "  " });
-                ctx.styled_label_add_text(&doc.filename);
+            let mut activated = None;
 
-                if let Some(path) = &doc.dir {
-                    ctx.styled_label_add_text("   ");
-                    ctx.styled_label_set_attributes(Attributes::Italic);
-                    ctx.styled_label_add_text(path.as_str());
+            match &state.go_to_file_results {
+                Some(results) => {
+                    for &index in results {
+                        if state.documents.update_at(index, |doc| {
+                            draw_go_to_file_entry(ctx, doc) == ListSelection::Activated
+                        }) {
+                            activated = Some(index);
+                            break;
+                        }
+                    }
                 }
+                None => {
+                    if state.documents.update_active(|doc| {
+                        draw_go_to_file_entry(ctx, doc) == ListSelection::Activated
+                    }) {
+                        ctx.needs_rerender();
+                    }
+                }
+            }
 
-                ctx.styled_list_item_end(false) == ListSelection::Activated
-            }) {
+            if let Some(index) = activated {
+                state.documents.set_active(index);
                 state.wants_go_to_file = false;
                 ctx.needs_rerender();
             }
@@ -345,4 +417,171 @@ pub fn draw_go_to_file(ctx: &mut Context, state: &mut State) {
     if ctx.modal_end() {
         state.wants_go_to_file = false;
     }
+
+    if !state.wants_go_to_file {
+        state.go_to_file_needle.clear();
+        state.go_to_file_results = None;
+    }
+}
+
+fn draw_go_to_file_entry(ctx: &mut Context, doc: &Document) -> ListSelection {
+    let tb = doc.buffer.borrow();
+
+    ctx.styled_list_item_begin();
+    ctx.attr_overflow(Overflow::TruncateTail);
+    ctx.styled_label_add_text(if tb.is_dirty() { "* " } else { "  " });
+    ctx.styled_label_add_text(&doc.filename);
+
+    if let Some(path) = &doc.dir {
+        ctx.styled_label_add_text("   ");
+        ctx.styled_label_set_attributes(Attributes::Italic);
+        ctx.styled_label_add_text(path.as_str());
+    }
+
+    ctx.styled_list_item_end(false)
+}
+
+fn go_to_file_update_list(state: &mut State) {
+    state.go_to_file_results = None;
+
+    let needle = state.go_to_file_needle.trim_ascii();
+    if needle.is_empty() {
+        return;
+    }
+
+    let scratch = scratch_arena(None);
+    let mut matches = Vec::new_in(&*scratch);
+
+    state.documents.for_each(|index, doc| {
+        let local_scratch = scratch_arena(Some(&scratch));
+        let (name_score, _) = score_fuzzy(&local_scratch, &doc.filename, needle, true);
+        let (dir_score, _) = match &doc.dir {
+            Some(dir) => score_fuzzy(&local_scratch, dir.as_str(), needle, true),
+            None => (0, Vec::new_in(&*local_scratch)),
+        };
+        let score = name_score.max(dir_score);
+
+        if score > 0 {
+            matches.push((score, index));
+        }
+    });
+
+    matches.sort_by(|a, b| b.0.cmp(&a.0));
+    state.go_to_file_results = Some(Vec::from_iter(matches.iter().map(|(_, index)| *index)));
+}
+
+/// Renders the active Markdown document as styled text instead of raw
+/// source. Toggled via the "markdown-preview" status bar button.
+pub fn draw_markdown_preview(ctx: &mut Context, state: &mut State) {
+    let Some(doc) = state.documents.active() else { return };
+    let tb = doc.buffer.borrow();
+    let mut md_state = markdown::MarkdownState::default();
+
+    ctx.list_begin("markdown-preview");
+    for line in 0..tb.logical_line_count() {
+        let mut spans = Vec::new();
+        markdown::line_to_spans(&mut md_state, tb.line_text(line), &mut spans);
+
+        ctx.styled_list_item_begin();
+        ctx.attr_overflow(Overflow::TruncateTail);
+        markdown::draw_spans(ctx, &spans);
+        ctx.styled_list_item_end(false);
+    }
+    ctx.list_end();
+}
+
+/// A single entry in the "Go to Symbol" outline: a Markdown heading, or
+/// (for non-Markdown source) a line matching one of [`SYMBOL_PATTERNS`].
+struct SymbolEntry {
+    line: CoordType,
+    level: u8,
+    title: String,
+}
+
+/// Line-leading patterns recognized as symbols in non-Markdown source.
+/// Not a real parser -- just enough to give a useful outline without a
+/// per-language grammar.
+const SYMBOL_PATTERNS: &[&str] = &["pub fn ", "fn ", "pub struct ", "struct ", "impl ", "enum ", "trait "];
+
+fn collect_symbols(filename: &str, tb: &TextBuffer) -> Vec<SymbolEntry> {
+    let is_markdown = filename.ends_with(".md") || filename.ends_with(".markdown");
+    let mut symbols = Vec::new();
+
+    for line in 0..tb.logical_line_count() {
+        let text = tb.line_text(line);
+
+        if is_markdown {
+            if let Some((level, title)) = markdown::atx_heading(text) {
+                symbols.push(SymbolEntry { line, level, title: title.to_string() });
+            }
+            continue;
+        }
+
+        let trimmed = text.trim_start();
+        for pattern in SYMBOL_PATTERNS {
+            if let Some(rest) = trimmed.strip_prefix(pattern) {
+                let title = rest.split(['(', '<', '{', ':']).next().unwrap_or(rest).trim();
+                symbols.push(SymbolEntry { line, level: 1, title: title.to_string() });
+                break;
+            }
+        }
+    }
+
+    symbols
+}
+
+pub fn draw_go_to_symbol(ctx: &mut Context, state: &mut State) {
+    ctx.modal_begin("go-to-symbol", loc(LocId::ViewGoToSymbol));
+    {
+        let width = (ctx.size().width - 20).max(10);
+        let height = (ctx.size().height - 10).max(10);
+
+        ctx.scrollarea_begin("scrollarea", Size { width, height });
+        ctx.attr_background_rgba(ctx.indexed_alpha(IndexedColor::Black, 1, 4));
+        ctx.inherit_focus();
+
+        if let Some(doc) = state.documents.active() {
+            let mut tb = doc.buffer.borrow_mut();
+            let symbols = collect_symbols(&doc.filename, &tb);
+            let cursor_line = tb.cursor_logical_pos().y;
+
+            // The "current" entry is the last heading/symbol at or above
+            // the cursor's line, so the picker opens pre-focused on where
+            // the user already is in the document.
+            let current = symbols.iter().rposition(|s| s.line <= cursor_line);
+            let mut activated = None;
+
+            ctx.list_begin("symbols");
+            ctx.inherit_focus();
+
+            for (i, symbol) in symbols.iter().enumerate() {
+                ctx.styled_list_item_begin();
+                ctx.attr_overflow(Overflow::TruncateTail);
+                ctx.attr_padding(Rect { left: (symbol.level as CoordType - 1) * 2, ..Rect::default() });
+
+                if Some(i) == current {
+                    ctx.focus_on_first_present();
+                }
+
+                ctx.styled_label_add_text(&symbol.title);
+
+                if ctx.styled_list_item_end(Some(i) == current) == ListSelection::Activated {
+                    activated = Some(symbol.line);
+                }
+            }
+
+            ctx.list_end();
+
+            if let Some(line) = activated {
+                tb.set_cursor_logical_pos(Point { x: 0, y: line });
+                state.wants_go_to_symbol = false;
+                ctx.needs_rerender();
+            }
+        }
+
+        ctx.scrollarea_end();
+    }
+    if ctx.modal_end() {
+        state.wants_go_to_symbol = false;
+    }
 }
@@ -0,0 +1,275 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Read-only "preview" rendering of Markdown source.
+//!
+//! This is a single-pass converter: it walks the buffer line by line,
+//! tracking only whether it is inside a fenced code block, and emits
+//! `(text, Attributes, color)` spans that are fed straight into
+//! `styled_label_add_text` + `styled_label_set_attributes`. Inline styles
+//! do not nest: the text inside a bold, italic, strikethrough or link span
+//! is emitted as-is, without further inline parsing. There's no intermediate AST -- Markdown
+//! here is a presentation hint layered on top of the plain-text buffer,
+//! not a separate document model.
+
+use edit::framebuffer::{Attributes, IndexedColor};
+use edit::tui::Context;
+
+/// A single run of text plus the attributes/coloring it should be drawn
+/// with. For `[text](url)` links only the text is kept, underlined and
+/// colored; the URL itself is never rendered.
+pub struct Span {
+    pub text: String,
+    pub attr: Attributes,
+    pub fg: Option<IndexedColor>,
+    pub is_code: bool,
+}
+
+/// Per-line fence tracking, threaded through successive calls to
+/// [`line_to_spans`] so that fenced code blocks spanning multiple lines
+/// suppress inline parsing on every line in between.
+#[derive(Default)]
+pub struct MarkdownState {
+    in_fence: bool,
+}
+
+/// Converts a single line of Markdown source into display spans.
+///
+/// Unterminated spans (e.g. `**bold` with no closing `**`) fall back to
+/// literal text, and code spans/fences suppress all other inline parsing.
+pub fn line_to_spans(state: &mut MarkdownState, line: &str, out: &mut Vec<Span>) {
+    let trimmed = line.trim_start();
+
+    if trimmed.starts_with("```") {
+        state.in_fence = !state.in_fence;
+        out.push(Span { text: line.to_string(), attr: Attributes::None, fg: None, is_code: true });
+        return;
+    }
+
+    if state.in_fence {
+        out.push(Span { text: line.to_string(), attr: Attributes::None, fg: None, is_code: true });
+        return;
+    }
+
+    if let Some((level, text)) = atx_heading(line) {
+        out.push(Span {
+            text: text.to_string(),
+            attr: Attributes::Bold,
+            fg: Some(heading_color(level)),
+            is_code: false,
+        });
+        return;
+    }
+
+    parse_inline(line, out);
+}
+
+/// Recognizes an ATX heading (1-6 `#` followed by a space) and returns
+/// its level plus the heading text with the marker stripped. Shared with
+/// the "Go to Symbol" outline, which needs the same heading detection.
+pub fn atx_heading(line: &str) -> Option<(u8, &str)> {
+    let trimmed = line.trim_start();
+    let hashes = trimmed.bytes().take_while(|&b| b == b'#').count();
+    if hashes >= 1 && hashes <= 6 && trimmed.as_bytes().get(hashes) == Some(&b' ') {
+        Some((hashes as u8, trimmed[hashes..].trim_start()))
+    } else {
+        None
+    }
+}
+
+fn heading_color(level: u8) -> IndexedColor {
+    // Scale the foreground color by heading level: h1 is the brightest,
+    // h6 fades toward the default text color.
+    match level {
+        1 => IndexedColor::BrightCyan,
+        2 => IndexedColor::Cyan,
+        3 => IndexedColor::BrightBlue,
+        4 => IndexedColor::Blue,
+        5 => IndexedColor::BrightBlack,
+        _ => IndexedColor::Black,
+    }
+}
+
+fn parse_inline(line: &str, out: &mut Vec<Span>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    let mut plain_start = 0;
+    let mut attr = Attributes::None;
+
+    macro_rules! flush_plain {
+        ($end:expr) => {
+            if $end > plain_start {
+                out.push(Span { text: line[plain_start..$end].to_string(), attr, fg: None, is_code: false });
+            }
+        };
+    }
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c == b'\\' && i + 1 < bytes.len() {
+            // Backslash escapes: the following *character* (which may be
+            // multi-byte) is literal, and the backslash itself is dropped
+            // from the rendered text.
+            if let Some(ch) = line[i + 1..].chars().next() {
+                flush_plain!(i);
+                out.push(Span { text: ch.to_string(), attr, fg: None, is_code: false });
+                i += 1 + ch.len_utf8();
+                plain_start = i;
+                continue;
+            }
+        }
+
+        if c == b'`' {
+            if let Some(close) = find_closing(line, i + 1, b'`') {
+                flush_plain!(i);
+                out.push(Span {
+                    text: line[i + 1..close].to_string(),
+                    attr: Attributes::None,
+                    fg: None,
+                    is_code: true,
+                });
+                i = close + 1;
+                plain_start = i;
+                continue;
+            }
+            // Unterminated code span: fall back to literal text.
+            i += 1;
+            continue;
+        }
+
+        if c == b'~' && bytes.get(i + 1) == Some(&b'~') {
+            if let Some(close) = find_closing_pair(line, i + 2, b'~') {
+                flush_plain!(i);
+                out.push(Span {
+                    text: line[i + 2..close].to_string(),
+                    attr: attr | Attributes::Strikethrough,
+                    fg: None,
+                    is_code: false,
+                });
+                i = close + 2;
+                plain_start = i;
+                continue;
+            }
+            i += 2;
+            continue;
+        }
+
+        if (c == b'*' || c == b'_') && bytes.get(i + 1) == Some(&c) {
+            if let Some(close) = find_closing_pair(line, i + 2, c) {
+                flush_plain!(i);
+                out.push(Span {
+                    text: line[i + 2..close].to_string(),
+                    attr: attr | Attributes::Bold,
+                    fg: None,
+                    is_code: false,
+                });
+                i = close + 2;
+                plain_start = i;
+                continue;
+            }
+            i += 1;
+            continue;
+        }
+
+        if c == b'*' || c == b'_' {
+            if let Some(close) = find_closing(line, i + 1, c) {
+                flush_plain!(i);
+                out.push(Span {
+                    text: line[i + 1..close].to_string(),
+                    attr: attr | Attributes::Italic,
+                    fg: None,
+                    is_code: false,
+                });
+                i = close + 1;
+                plain_start = i;
+                continue;
+            }
+            i += 1;
+            continue;
+        }
+
+        if c == b'[' {
+            if let Some((text_end, url_start, url_end)) = find_link(line, i + 1) {
+                flush_plain!(i);
+                out.push(Span {
+                    text: line[i + 1..text_end].to_string(),
+                    attr: attr | Attributes::Underlined,
+                    fg: Some(IndexedColor::BrightBlue),
+                    is_code: false,
+                });
+                let _url = &line[url_start..url_end];
+                i = url_end + 1;
+                plain_start = i;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+
+    flush_plain!(bytes.len());
+}
+
+/// Finds the offset of the next standalone `needle` byte, treating an
+/// escaping backslash as making it non-matching.
+fn find_closing(line: &str, from: usize, needle: u8) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut i = from;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            i += 2;
+            continue;
+        }
+        if bytes[i] == needle {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Like [`find_closing`], but requires two consecutive `needle` bytes
+/// (for `**bold**` / `~~strike~~`).
+fn find_closing_pair(line: &str, from: usize, needle: u8) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut i = from;
+    while i + 1 < bytes.len() {
+        if bytes[i] == b'\\' {
+            i += 2;
+            continue;
+        }
+        if bytes[i] == needle && bytes[i + 1] == needle {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Parses a `[text](url)` link starting just after the opening `[`.
+/// Returns (text_end, url_start, url_end).
+fn find_link(line: &str, from: usize) -> Option<(usize, usize, usize)> {
+    let text_end = find_closing(line, from, b']')?;
+    let bytes = line.as_bytes();
+    if bytes.get(text_end + 1) != Some(&b'(') {
+        return None;
+    }
+    let url_start = text_end + 2;
+    let url_end = find_closing(line, url_start, b')')?;
+    Some((text_end, url_start, url_end))
+}
+
+/// Renders one already-converted line into the TUI via `styled_label`.
+pub fn draw_spans(ctx: &mut Context, spans: &[Span]) {
+    for span in spans {
+        if span.is_code {
+            ctx.attr_background_rgba(ctx.indexed_alpha(IndexedColor::White, 1, 6));
+        }
+        if let Some(fg) = span.fg {
+            ctx.attr_foreground_indexed(fg);
+        }
+        ctx.styled_label_set_attributes(span.attr);
+        ctx.styled_label_add_text(&span.text);
+    }
+}
@@ -0,0 +1,392 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! SIMD Adler-32, plus a rolling-window variant for content-defined chunk
+//! matching.
+//!
+//! Reloading a file from disk, or diffing against the last-saved buffer, is
+//! much cheaper if we can first tell which blocks are unchanged or merely
+//! moved, instead of falling back to a byte-by-byte diff. Adler-32 is the
+//! classic checksum for this: it's weak enough to compute (and *update*
+//! incrementally as a window slides byte-by-byte) but strong enough to
+//! rule out false positives cheaply, with a full byte comparison only once
+//! two windows' checksums actually collide.
+//!
+//! The bulk checksum below uses the same NMAX-blocking technique as zlib:
+//! `s1`/`s2` are accumulated as plain `u32` sums for up to [`NMAX`] bytes at
+//! a time -- the largest chunk guaranteed not to overflow a `u32` before the
+//! next `mod 65521` reduction -- and the per-chunk SIMD kernels compute the
+//! positionally-weighted `s2` contribution via a widening multiply-add,
+//! mirroring the byte-lane tricks used throughout `simd::lines_fwd`.
+
+const MOD_ADLER: u32 = 65521;
+const NMAX: usize = 5552;
+
+/// Computes the Adler-32 checksum of `data`.
+pub fn adler32(data: &[u8]) -> u32 {
+    adler32_with_seed(data, 1)
+}
+
+/// Computes the Adler-32 checksum of `data`, continuing from a previous
+/// `(s1, s2)` pair packed into `seed` the same way the return value is
+/// (`s2 << 16 | s1`). Lets callers checksum a buffer in pieces.
+pub fn adler32_with_seed(data: &[u8], seed: u32) -> u32 {
+    let mut s1 = seed & 0xffff;
+    let mut s2 = (seed >> 16) & 0xffff;
+    unsafe { adler32_raw(data.as_ptr(), data.len(), &mut s1, &mut s2) };
+    (s2 << 16) | s1
+}
+
+unsafe fn adler32_raw(mut data: *const u8, mut len: usize, s1: &mut u32, s2: &mut u32) {
+    unsafe {
+        while len > 0 {
+            let chunk_len = len.min(NMAX);
+            adler32_chunk(data, chunk_len, s1, s2);
+            *s1 %= MOD_ADLER;
+            *s2 %= MOD_ADLER;
+            data = data.add(chunk_len);
+            len -= chunk_len;
+        }
+    }
+}
+
+unsafe fn adler32_chunk(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    #[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+    return unsafe { ADLER32_DISPATCH(data, len, s1, s2) };
+
+    #[cfg(target_arch = "aarch64")]
+    return unsafe { adler32_chunk_neon(data, len, s1, s2) };
+
+    #[allow(unreachable_code)]
+    return unsafe { adler32_chunk_fallback(data, len, s1, s2) };
+}
+
+unsafe fn adler32_chunk_fallback(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    unsafe {
+        for i in 0..len {
+            *s1 += *data.add(i) as u32;
+            *s2 += *s1;
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+static mut ADLER32_DISPATCH: unsafe fn(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) =
+    adler32_dispatch;
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn adler32_dispatch(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    let func = if is_x86_feature_detected!("avx2") { adler32_chunk_avx2 } else { adler32_chunk_fallback };
+    unsafe { ADLER32_DISPATCH = func };
+    unsafe { func(data, len, s1, s2) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn adler32_chunk_avx2(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    unsafe {
+        use std::arch::x86_64::*;
+
+        #[inline(always)]
+        unsafe fn hsum_epi32(v: __m256i) -> u32 {
+            unsafe {
+                let hi = _mm256_extracti128_si256::<1>(v);
+                let lo = _mm256_castsi256_si128(v);
+                let sum = _mm_add_epi32(lo, hi);
+                let shuf = _mm_shuffle_epi32::<0b00_00_11_10>(sum);
+                let sum = _mm_add_epi32(sum, shuf);
+                let shuf = _mm_shuffle_epi32::<0b00_00_00_01>(sum);
+                let sum = _mm_add_epi32(sum, shuf);
+                _mm_cvtsi128_si32(sum) as u32
+            }
+        }
+
+        #[inline(always)]
+        unsafe fn hsum_epi64(v: __m256i) -> u64 {
+            unsafe {
+                let hi = _mm256_extracti128_si256::<1>(v);
+                let lo = _mm256_castsi256_si128(v);
+                let sum = _mm_add_epi64(lo, hi);
+                let shuf = _mm_shuffle_epi32::<0b11_10_11_10>(sum);
+                let sum = _mm_add_epi64(sum, shuf);
+                _mm_cvtsi128_si64(sum) as u64
+            }
+        }
+
+        // Descending per-byte weights [32, 31, .., 1], consumed by
+        // `vpmaddubsw` (unsigned byte x signed byte -> i16) then folded
+        // pairwise into 8 x i32 lanes by `vpmaddwd`.
+        let weights = _mm256_setr_epi8(
+            32, 31, 30, 29, 28, 27, 26, 25, 24, 23, 22, 21, 20, 19, 18, 17, 16, 15, 14, 13, 12, 11,
+            10, 9, 8, 7, 6, 5, 4, 3, 2, 1,
+        );
+        let ones = _mm256_set1_epi16(1);
+
+        let s1_start = *s1;
+        let mut s1_acc: u32 = 0;
+        let mut s2_acc: u32 = 0;
+
+        let mut i = 0;
+        while i + 32 <= len {
+            let v = _mm256_loadu_si256(data.add(i) as *const _);
+
+            let weighted = _mm256_maddubs_epi16(v, weights);
+            let weighted = _mm256_madd_epi16(weighted, ones);
+            let weighted_sum = hsum_epi32(weighted);
+
+            let byte_sum = hsum_epi64(_mm256_sad_epu8(v, _mm256_setzero_si256())) as u32;
+
+            // Every earlier byte's position weight grows by one full block
+            // each time we start a new one, so inflate `s2_acc` by the
+            // block size times the running byte sum *before* folding in
+            // this block's own (locally-weighted) contribution.
+            s2_acc = s2_acc.wrapping_add(32u32.wrapping_mul(s1_acc)).wrapping_add(weighted_sum);
+            s1_acc = s1_acc.wrapping_add(byte_sum);
+
+            i += 32;
+        }
+
+        *s2 = (*s2).wrapping_add((i as u32).wrapping_mul(s1_start)).wrapping_add(s2_acc);
+        *s1 = s1_start.wrapping_add(s1_acc);
+
+        adler32_chunk_fallback(data.add(i), len - i, s1, s2);
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+unsafe fn adler32_dispatch(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    use std::arch::is_loongarch_feature_detected;
+
+    let func = if is_loongarch_feature_detected!("lasx") {
+        adler32_chunk_lasx
+    } else if is_loongarch_feature_detected!("lsx") {
+        adler32_chunk_lsx
+    } else {
+        adler32_chunk_fallback
+    };
+    unsafe { ADLER32_DISPATCH = func };
+    unsafe { func(data, len, s1, s2) }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lasx")]
+unsafe fn adler32_chunk_lasx(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    unsafe {
+        use std::arch::loongarch64::*;
+        use std::mem::transmute as T;
+
+        #[inline(always)]
+        unsafe fn horizontal_sum_w(sum: v8i32) -> u32 {
+            unsafe {
+                let sum = lasx_xvhaddw_d_w(sum, sum);
+                let sum = lasx_xvhaddw_q_d(T(sum), T(sum));
+                let tmp = lasx_xvpermi_q::<1>(T(sum), T(sum));
+                let sum = lasx_xvadd_w(T(sum), T(tmp));
+                lasx_xvpickve2gr_wu::<0>(sum)
+            }
+        }
+
+        let weights: [i8; 32] = [
+            32, 31, 30, 29, 28, 27, 26, 25, 24, 23, 22, 21, 20, 19, 18, 17, 16, 15, 14, 13, 12, 11,
+            10, 9, 8, 7, 6, 5, 4, 3, 2, 1,
+        ];
+        let weights = lasx_xvld::<0>(weights.as_ptr() as *const _);
+
+        let s1_start = *s1;
+        let mut s1_acc: u32 = 0;
+        let mut s2_acc: u32 = 0;
+
+        let mut i = 0;
+        while i + 32 <= len {
+            let v = lasx_xvld::<0>(data.add(i) as *const _);
+
+            // Widening unsigned(v) * signed(weights) multiply-add, folded
+            // from even/odd halfword lanes into 32-bit lanes.
+            let even = lasx_xvmulwev_h_bu_b(v, weights);
+            let prod = lasx_xvmaddwod_h_bu_b(even, v, weights);
+            let weighted = lasx_xvhaddw_w_h(prod, prod);
+            let weighted_sum = horizontal_sum_w(weighted);
+
+            let byte_sum_v = lasx_xvhaddw_hu_bu(T(v), T(v));
+            let byte_sum_v = lasx_xvhaddw_wu_hu(T(byte_sum_v), T(byte_sum_v));
+            let byte_sum = horizontal_sum_w(T(byte_sum_v));
+
+            s2_acc = s2_acc.wrapping_add(32u32.wrapping_mul(s1_acc)).wrapping_add(weighted_sum);
+            s1_acc = s1_acc.wrapping_add(byte_sum);
+
+            i += 32;
+        }
+
+        *s2 = (*s2).wrapping_add((i as u32).wrapping_mul(s1_start)).wrapping_add(s2_acc);
+        *s1 = s1_start.wrapping_add(s1_acc);
+
+        adler32_chunk_fallback(data.add(i), len - i, s1, s2);
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lsx")]
+unsafe fn adler32_chunk_lsx(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    unsafe {
+        use std::arch::loongarch64::*;
+        use std::mem::transmute as T;
+
+        #[inline(always)]
+        unsafe fn horizontal_sum_w(sum: v4i32) -> u32 {
+            unsafe {
+                let sum = lsx_vhaddw_d_w(sum, sum);
+                let sum = lsx_vhaddw_q_d(T(sum), T(sum));
+                lsx_vpickve2gr_wu::<0>(T(sum))
+            }
+        }
+
+        let weights: [i8; 16] = [16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1];
+        let weights = lsx_vld::<0>(weights.as_ptr() as *const _);
+
+        let s1_start = *s1;
+        let mut s1_acc: u32 = 0;
+        let mut s2_acc: u32 = 0;
+
+        let mut i = 0;
+        while i + 16 <= len {
+            let v = lsx_vld::<0>(data.add(i) as *const _);
+
+            let even = lsx_vmulwev_h_bu_b(v, weights);
+            let prod = lsx_vmaddwod_h_bu_b(even, v, weights);
+            let weighted = lsx_vhaddw_w_h(prod, prod);
+            let weighted_sum = horizontal_sum_w(weighted);
+
+            let byte_sum_v = lsx_vhaddw_hu_bu(T(v), T(v));
+            let byte_sum_v = lsx_vhaddw_wu_hu(T(byte_sum_v), T(byte_sum_v));
+            let byte_sum = horizontal_sum_w(T(byte_sum_v));
+
+            s2_acc = s2_acc.wrapping_add(16u32.wrapping_mul(s1_acc)).wrapping_add(weighted_sum);
+            s1_acc = s1_acc.wrapping_add(byte_sum);
+
+            i += 16;
+        }
+
+        *s2 = (*s2).wrapping_add((i as u32).wrapping_mul(s1_start)).wrapping_add(s2_acc);
+        *s1 = s1_start.wrapping_add(s1_acc);
+
+        adler32_chunk_fallback(data.add(i), len - i, s1, s2);
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn adler32_chunk_neon(data: *const u8, len: usize, s1: &mut u32, s2: &mut u32) {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        let weights_hi: [u8; 8] = [16, 15, 14, 13, 12, 11, 10, 9];
+        let weights_lo: [u8; 8] = [8, 7, 6, 5, 4, 3, 2, 1];
+        let w_hi = vld1_u8(weights_hi.as_ptr());
+        let w_lo = vld1_u8(weights_lo.as_ptr());
+
+        let s1_start = *s1;
+        let mut s1_acc: u32 = 0;
+        let mut s2_acc: u32 = 0;
+
+        let mut i = 0;
+        while i + 16 <= len {
+            let v = vld1q_u8(data.add(i));
+            let v_lo = vget_low_u8(v);
+            let v_hi = vget_high_u8(v);
+
+            let weighted = vaddq_u16(vmull_u8(v_lo, w_hi), vmull_u8(v_hi, w_lo));
+            let weighted_sum = vaddlvq_u16(weighted);
+
+            let byte_sum = vaddlvq_u8(v) as u32;
+
+            s2_acc = s2_acc.wrapping_add(16u32.wrapping_mul(s1_acc)).wrapping_add(weighted_sum);
+            s1_acc = s1_acc.wrapping_add(byte_sum);
+
+            i += 16;
+        }
+
+        *s2 = (*s2).wrapping_add((i as u32).wrapping_mul(s1_start)).wrapping_add(s2_acc);
+        *s1 = s1_start.wrapping_add(s1_acc);
+
+        adler32_chunk_fallback(data.add(i), len - i, s1, s2);
+    }
+}
+
+/// A fixed-size rolling Adler-32 window, for content-defined chunk matching:
+/// sliding the window one byte at a time and looking for a checksum that
+/// matches some earlier-seen block is far cheaper than recomputing the
+/// whole-window checksum from scratch at every offset.
+pub struct RollingAdler32 {
+    s1: u32,
+    s2: u32,
+    window_len: u32,
+}
+
+impl RollingAdler32 {
+    /// Seeds the rolling window from its initial contents.
+    pub fn new(initial_window: &[u8]) -> Self {
+        let packed = adler32(initial_window);
+        Self { s1: packed & 0xffff, s2: (packed >> 16) & 0xffff, window_len: initial_window.len() as u32 }
+    }
+
+    /// Slides the window forward by one byte: `old_byte` leaves at the
+    /// front, `new_byte` enters at the back, and the window length stays
+    /// the same.
+    pub fn roll(&mut self, old_byte: u8, new_byte: u8) {
+        let old = old_byte as i64;
+        let new = new_byte as i64;
+        let m = MOD_ADLER as i64;
+
+        let s1 = (self.s1 as i64 - old + new).rem_euclid(m);
+        let s2 = (self.s2 as i64 - (self.window_len as i64) * old + s1 - 1).rem_euclid(m);
+
+        self.s1 = s1 as u32;
+        self.s2 = s2 as u32;
+    }
+
+    /// The current checksum of the window, packed the same way
+    /// [`adler32`]'s return value is.
+    pub fn value(&self) -> u32 {
+        (self.s2 << 16) | self.s1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::simd::test::*;
+
+    fn naive_adler32(data: &[u8]) -> u32 {
+        let mut s1 = 1u32;
+        let mut s2 = 0u32;
+        for &b in data {
+            s1 = (s1 + b as u32) % MOD_ADLER;
+            s2 = (s2 + s1) % MOD_ADLER;
+        }
+        (s2 << 16) | s1
+    }
+
+    #[test]
+    fn matches_naive_reference() {
+        for &len in &[0, 1, 15, 16, 17, 31, 32, 33, 1000, NMAX, NMAX + 1, NMAX * 2 + 37] {
+            let text = generate_random_text(len);
+            let bytes = text.as_bytes();
+            assert_eq!(naive_adler32(bytes), adler32(bytes), "mismatch at len={len}");
+        }
+    }
+
+    #[test]
+    fn rolling_window_matches_recompute() {
+        let text = generate_random_text(2048);
+        let bytes = text.as_bytes();
+        let window_len = 64;
+
+        let mut rolling = RollingAdler32::new(&bytes[..window_len]);
+        assert_eq!(rolling.value(), adler32(&bytes[..window_len]));
+
+        for start in 1..=(bytes.len() - window_len) {
+            rolling.roll(bytes[start - 1], bytes[start + window_len - 1]);
+            let expected = adler32(&bytes[start..start + window_len]);
+            assert_eq!(rolling.value(), expected, "mismatch at start={start}");
+        }
+    }
+}
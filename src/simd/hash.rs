@@ -0,0 +1,518 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! An xxh3-64-style content hash for change detection.
+//!
+//! Incremental rendering and undo coalescing both boil down to the same
+//! question: "did this line / viewport region change since last frame?"
+//! Comparing raw bytes is exactly as expensive as re-rendering, so instead
+//! we hash each region and compare the (much smaller) hashes. This module
+//! implements that hash using the same stripe-accumulator design as
+//! xxh3-64: 8 running `u64` accumulators are folded over 64-byte stripes of
+//! input, mixed against a fixed secret, and periodically "scrambled" to keep
+//! the accumulators from drifting into a low-entropy state on long inputs.
+//!
+//! Like [`super::lines_fwd`], the bulk path is dispatched at runtime to the
+//! best available instruction set, with small inputs (the overwhelmingly
+//! common case for single-line hashing) served by dedicated short paths
+//! that never touch the stripe loop at all.
+
+const STRIPE_LEN: usize = 64;
+const ACC_NB: usize = 8;
+const SECRET_SIZE: usize = 192;
+const STRIPES_PER_BLOCK: usize = (SECRET_SIZE - STRIPE_LEN) / 8;
+
+const PRIME64_1: u64 = 0x9E3779B185EBCA87;
+const PRIME64_2: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME64_3: u64 = 0x165667B19E3779F9;
+
+const ACC_INIT: [u64; ACC_NB] = [
+    PRIME64_1,
+    PRIME64_2,
+    PRIME64_3,
+    0x85EBCA77C2B2AE63,
+    0x27D4EB2F165667C5,
+    0x2545F4914F6CDD1D,
+    0x9E3779B97F4A7C15,
+    0xBF58476D1CE4E5B9,
+];
+
+// XXH3 hardcodes a 192-byte secret derived from digits of pi. We don't need
+// bit-for-bit compatibility with upstream xxxHash (this hash never leaves
+// the process), only a fixed, well-mixed byte sequence -- so instead of
+// hand-copying a 192-byte table, one is generated at compile time from a
+// small LCG.
+const fn generate_secret() -> [u8; SECRET_SIZE] {
+    let mut secret = [0u8; SECRET_SIZE];
+    let mut state: u64 = 0x9E3779B185EBCA87;
+    let mut i = 0;
+    while i < SECRET_SIZE {
+        state = state.wrapping_mul(0x2545_F491_4F6C_DD1D).wrapping_add(0xC2B2_AE3D_27D4_EB4F);
+        secret[i] = (state >> 56) as u8;
+        i += 1;
+    }
+    secret
+}
+
+const SECRET: [u8; SECRET_SIZE] = generate_secret();
+
+#[inline(always)]
+unsafe fn read_u32_le(p: *const u8) -> u32 {
+    unsafe { u32::from_le_bytes(*(p as *const [u8; 4])) }
+}
+
+#[inline(always)]
+unsafe fn read_u64_le(p: *const u8) -> u64 {
+    unsafe { u64::from_le_bytes(*(p as *const [u8; 8])) }
+}
+
+#[inline(always)]
+fn avalanche(mut x: u64) -> u64 {
+    x ^= x >> 37;
+    x = x.wrapping_mul(PRIME64_2);
+    x ^= x >> 32;
+    x
+}
+
+/// Hashes an arbitrary byte slice.
+///
+/// Dispatches to dedicated `<=16`/`<=128`/`<=240` byte paths -- single lines
+/// almost always land here -- and only enters the stripe-accumulator loop
+/// for genuinely large regions.
+pub fn hash_bytes(data: &[u8]) -> u64 {
+    unsafe {
+        let p = data.as_ptr();
+        let len = data.len();
+        if len <= 16 {
+            hash_0to16(p, len)
+        } else if len <= 128 {
+            hash_17to128(p, len)
+        } else if len <= 240 {
+            hash_129to240(p, len)
+        } else {
+            hash_long(p, len)
+        }
+    }
+}
+
+unsafe fn hash_0to16(data: *const u8, len: usize) -> u64 {
+    unsafe {
+        if len == 0 {
+            return avalanche(read_u64_le(SECRET.as_ptr()) ^ read_u64_le(SECRET.as_ptr().add(8)));
+        }
+        if len < 4 {
+            let c1 = *data as u64;
+            let c2 = *data.add(len >> 1) as u64;
+            let c3 = *data.add(len - 1) as u64;
+            let combined = (c1 << 16) | (c2 << 24) | c3 | ((len as u64) << 8);
+            return avalanche(combined ^ read_u64_le(SECRET.as_ptr()));
+        }
+        if len <= 8 {
+            let lo = read_u32_le(data) as u64;
+            let hi = read_u32_le(data.add(len - 4)) as u64;
+            let combined = lo | (hi << 32);
+            return avalanche(combined ^ read_u64_le(SECRET.as_ptr().add(8)) ^ (len as u64));
+        }
+        let lo = read_u64_le(data) ^ read_u64_le(SECRET.as_ptr().add(24));
+        let hi = read_u64_le(data.add(len - 8)) ^ read_u64_le(SECRET.as_ptr().add(32));
+        avalanche(lo.wrapping_add(hi).wrapping_add(len as u64))
+    }
+}
+
+/// Mixes one 16-byte chunk against a secret window, xxh3-style:
+/// `(lo ^ secret_lo) * (hi ^ secret_hi)`, folded back into a single `u64`.
+#[inline(always)]
+unsafe fn mix16(data: *const u8, secret: *const u8) -> u64 {
+    unsafe {
+        let lo = read_u64_le(data) ^ read_u64_le(secret);
+        let hi = read_u64_le(data.add(8)) ^ read_u64_le(secret.add(8));
+        let m = (lo as u128).wrapping_mul(hi as u128);
+        (m as u64) ^ ((m >> 64) as u64)
+    }
+}
+
+unsafe fn hash_17to128(data: *const u8, len: usize) -> u64 {
+    unsafe {
+        let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+        let secret = SECRET.as_ptr();
+
+        // Mix in pairs of 16-byte chunks, one from the front and one from
+        // the back, walking inward; the number of pairs scales with len so
+        // that the front and back runs always meet or overlap, leaving no
+        // unread byte in the middle (the runs meet exactly, with no
+        // overlap, whenever len is a multiple of 32).
+        let chunks = len.div_ceil(32);
+        for i in 0..chunks {
+            acc = acc.wrapping_add(mix16(data.add(i * 16), secret.add(i * 16)));
+            acc = acc.wrapping_add(mix16(
+                data.add(len - (i + 1) * 16),
+                secret.add(SECRET_SIZE - (i + 1) * 16),
+            ));
+        }
+
+        avalanche(acc)
+    }
+}
+
+unsafe fn hash_129to240(data: *const u8, len: usize) -> u64 {
+    unsafe {
+        let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+        let secret = SECRET.as_ptr();
+
+        // Unlike hash_17to128, this walks forward-only in 16-byte strides
+        // (no back-anchored pairing): since full_chunks * 16 is always
+        // within 16 bytes of len, it always meets the final overlapping
+        // chunk below with no gap, however many strides that takes.
+        let full_chunks = len / 16;
+        for i in 0..full_chunks {
+            acc = acc.wrapping_add(mix16(data.add(i * 16), secret.add((i * 8) % (SECRET_SIZE - 16))));
+        }
+
+        // The trailing remainder is folded in via one final, possibly
+        // overlapping, 16-byte read anchored at the end of the input --
+        // the same overlapping-tail trick used throughout `simd::memset`.
+        acc = acc.wrapping_add(mix16(data.add(len - 16), secret.add(SECRET_SIZE - 16 - 7)));
+
+        avalanche(acc)
+    }
+}
+
+#[inline(always)]
+unsafe fn accumulate_stripe_scalar(acc: &mut [u64; ACC_NB], input: *const u8, secret: *const u8) {
+    unsafe {
+        let mut lanes = [0u64; ACC_NB];
+        for (i, lane) in lanes.iter_mut().enumerate() {
+            *lane = read_u64_le(input.add(i * 8));
+        }
+        for i in 0..ACC_NB {
+            let secret_lane = read_u64_le(secret.add(i * 8));
+            let data_key = lanes[i] ^ secret_lane;
+            let lo = data_key as u32;
+            let hi = (data_key >> 32) as u32;
+            acc[i] = acc[i].wrapping_add(lanes[i ^ 1]);
+            acc[i] = acc[i].wrapping_add((lo as u64).wrapping_mul(hi as u64));
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn scramble_acc_scalar(acc: &mut [u64; ACC_NB], secret: *const u8) {
+    unsafe {
+        for (i, a) in acc.iter_mut().enumerate() {
+            let key = read_u64_le(secret.add(i * 8));
+            let mut v = *a ^ (*a >> 47);
+            v ^= key;
+            *a = v.wrapping_mul(PRIME64_1);
+        }
+    }
+}
+
+fn merge_acc(acc: &[u64; ACC_NB], len: usize) -> u64 {
+    let mut result = (len as u64).wrapping_mul(PRIME64_1);
+    let mut i = 0;
+    while i < ACC_NB {
+        let m = (acc[i] ^ PRIME64_2.rotate_left(i as u32)) as u128
+            * (acc[i + 1] ^ PRIME64_3.rotate_right(i as u32)) as u128;
+        result = result.wrapping_add((m as u64) ^ ((m >> 64) as u64));
+        i += 2;
+    }
+    avalanche(result)
+}
+
+unsafe fn hash_long(data: *const u8, len: usize) -> u64 {
+    #[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+    return unsafe { HASH_LONG_DISPATCH(data, len) };
+
+    #[cfg(target_arch = "aarch64")]
+    return unsafe { hash_long_neon(data, len) };
+
+    #[allow(unreachable_code)]
+    return unsafe { hash_long_fallback(data, len) };
+}
+
+unsafe fn hash_long_fallback(data: *const u8, len: usize) -> u64 {
+    unsafe { hash_long_generic(data, len, accumulate_stripe_scalar, scramble_acc_scalar) }
+}
+
+/// Shared driver for the stripe loop: walks `data` in `STRIPE_LEN` blocks,
+/// calling `accumulate` per stripe and `scramble` every [`STRIPES_PER_BLOCK`]
+/// stripes, then folds in one final overlapping stripe anchored at the end
+/// of the input so short tails are never read out of bounds.
+#[inline(always)]
+unsafe fn hash_long_generic(
+    data: *const u8,
+    len: usize,
+    accumulate: unsafe fn(&mut [u64; ACC_NB], *const u8, *const u8),
+    scramble: unsafe fn(&mut [u64; ACC_NB], *const u8),
+) -> u64 {
+    unsafe {
+        let end = data.add(len);
+        let secret = SECRET.as_ptr();
+        let mut acc = ACC_INIT;
+        let mut cur = data;
+        let mut secret_offset = 0usize;
+        let mut stripe_count = 0usize;
+
+        // Leave at least one byte for the final overlapping stripe below.
+        let full_stripes = (len - 1) / STRIPE_LEN;
+        for _ in 0..full_stripes {
+            accumulate(&mut acc, cur, secret.add(secret_offset));
+            cur = cur.add(STRIPE_LEN);
+            secret_offset += 8;
+            stripe_count += 1;
+            if stripe_count == STRIPES_PER_BLOCK {
+                scramble(&mut acc, secret.add(SECRET_SIZE - STRIPE_LEN));
+                secret_offset = 0;
+                stripe_count = 0;
+            }
+        }
+
+        accumulate(&mut acc, end.sub(STRIPE_LEN), secret.add(SECRET_SIZE - STRIPE_LEN - 7));
+
+        merge_acc(&acc, len)
+    }
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+static mut HASH_LONG_DISPATCH: unsafe fn(data: *const u8, len: usize) -> u64 = hash_long_dispatch;
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn hash_long_dispatch(data: *const u8, len: usize) -> u64 {
+    let func = if is_x86_feature_detected!("avx2") { hash_long_avx2 } else { hash_long_fallback };
+    unsafe { HASH_LONG_DISPATCH = func };
+    unsafe { func(data, len) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn accumulate_stripe_avx2(acc: &mut [u64; ACC_NB], input: *const u8, secret: *const u8) {
+    unsafe {
+        use std::arch::x86_64::*;
+
+        for half in 0..2 {
+            let off = half * 32;
+            let data = _mm256_loadu_si256(input.add(off) as *const _);
+            let key = _mm256_loadu_si256(secret.add(off) as *const _);
+            let data_key = _mm256_xor_si256(data, key);
+
+            // acc[i] += data[i ^ 1]: swap adjacent 64-bit lanes within each
+            // 128-bit half.
+            let swapped = _mm256_shuffle_epi32::<0b01_00_11_10>(data);
+            let mut acc_v = _mm256_loadu_si256(acc.as_ptr().add(half * 4) as *const _);
+            acc_v = _mm256_add_epi64(acc_v, swapped);
+
+            // acc[i] += lo32(data_key) * hi32(data_key), as unsigned 32x32->64 multiplies.
+            let lo = _mm256_and_si256(data_key, _mm256_set1_epi64x(0xFFFF_FFFF));
+            let hi = _mm256_srli_epi64::<32>(data_key);
+            let mul = _mm256_mul_epu32(lo, hi);
+            acc_v = _mm256_add_epi64(acc_v, mul);
+
+            _mm256_storeu_si256(acc.as_mut_ptr().add(half * 4) as *mut _, acc_v);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn scramble_acc_avx2(acc: &mut [u64; ACC_NB], secret: *const u8) {
+    unsafe {
+        use std::arch::x86_64::*;
+
+        let prime = _mm256_set1_epi64x(PRIME64_1 as i64);
+        for half in 0..2 {
+            let off = half * 32;
+            let mut a = _mm256_loadu_si256(acc.as_ptr().add(half * 4) as *const _);
+            let key = _mm256_loadu_si256(secret.add(off) as *const _);
+            a = _mm256_xor_si256(a, _mm256_srli_epi64::<47>(a));
+            a = _mm256_xor_si256(a, key);
+            // There's no 64x64->64 multiply in AVX2, so fall back to the
+            // same 32x32->64 trick used for accumulation: split, multiply
+            // the two cross terms plus the low*low term, and recombine.
+            let a_lo = _mm256_and_si256(a, _mm256_set1_epi64x(0xFFFF_FFFF));
+            let a_hi = _mm256_srli_epi64::<32>(a);
+            let p_lo = _mm256_and_si256(prime, _mm256_set1_epi64x(0xFFFF_FFFF));
+            let p_hi = _mm256_srli_epi64::<32>(prime);
+            let lo_lo = _mm256_mul_epu32(a_lo, p_lo);
+            let lo_hi = _mm256_mul_epu32(a_lo, p_hi);
+            let hi_lo = _mm256_mul_epu32(a_hi, p_lo);
+            let mid = _mm256_add_epi64(lo_hi, hi_lo);
+            let result = _mm256_add_epi64(lo_lo, _mm256_slli_epi64::<32>(mid));
+            _mm256_storeu_si256(acc.as_mut_ptr().add(half * 4) as *mut _, result);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn hash_long_avx2(data: *const u8, len: usize) -> u64 {
+    unsafe { hash_long_generic(data, len, accumulate_stripe_avx2, scramble_acc_avx2) }
+}
+
+// Unlike `lines_fwd`'s pure byte-compare, xxh3's 32x32->64 cross multiplies
+// don't have a convenient single-instruction LASX/LSX equivalent, so
+// loongarch64 is served by the scalar accumulator rather than gaining
+// dedicated kernels here.
+#[cfg(target_arch = "loongarch64")]
+unsafe fn hash_long_dispatch(data: *const u8, len: usize) -> u64 {
+    unsafe { HASH_LONG_DISPATCH = hash_long_fallback };
+    unsafe { hash_long_fallback(data, len) }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn accumulate_stripe_neon(acc: &mut [u64; ACC_NB], input: *const u8, secret: *const u8) {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        for half in 0..4 {
+            let off = half * 16;
+            let data = vld1q_u8(input.add(off));
+            let key = vld1q_u8(secret.add(off));
+            let data_key = veorq_u8(data, key);
+            let data_key = vreinterpretq_u64_u8(data_key);
+            let data64 = vreinterpretq_u64_u8(data);
+
+            let swapped = vextq_u64(data64, data64, 1);
+            let mut acc_v = vld1q_u64(acc.as_ptr().add(half * 2));
+            acc_v = vaddq_u64(acc_v, swapped);
+
+            let lo = vmovn_u64(data_key);
+            let hi = vmovn_u64(vshrq_n_u64::<32>(data_key));
+            let mul = vmull_u32(lo, hi);
+            acc_v = vaddq_u64(acc_v, mul);
+
+            vst1q_u64(acc.as_mut_ptr().add(half * 2), acc_v);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[target_feature(enable = "neon")]
+unsafe fn scramble_acc_neon(acc: &mut [u64; ACC_NB], secret: *const u8) {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        for half in 0..4 {
+            let off = half * 16;
+            let mut a = vld1q_u64(acc.as_ptr().add(half * 2));
+            let key = vld1q_u8(secret.add(off));
+            let key = vreinterpretq_u64_u8(key);
+            a = veorq_u64(a, vshrq_n_u64::<47>(a));
+            a = veorq_u64(a, key);
+
+            let a_lo = vmovn_u64(a);
+            let a_hi = vmovn_u64(vshrq_n_u64::<32>(a));
+            let prime = vdupq_n_u64(PRIME64_1);
+            let p_lo = vmovn_u64(prime);
+            let p_hi = vmovn_u64(vshrq_n_u64::<32>(prime));
+
+            let lo_lo = vmull_u32(a_lo, p_lo);
+            let lo_hi = vmull_u32(a_lo, p_hi);
+            let hi_lo = vmull_u32(a_hi, p_lo);
+            let mid = vaddq_u64(lo_hi, hi_lo);
+            let result = vaddq_u64(lo_lo, vshlq_n_u64::<32>(mid));
+
+            vst1q_u64(acc.as_mut_ptr().add(half * 2), result);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn hash_long_neon(data: *const u8, len: usize) -> u64 {
+    unsafe { hash_long_generic(data, len, accumulate_stripe_neon, scramble_acc_neon) }
+}
+
+/// A streaming hasher for callers that build up a line or region piecemeal
+/// (e.g. while re-assembling a visible viewport from the gap buffer).
+///
+/// This is a buffering wrapper rather than a "true" streaming xxh3: it
+/// collects all fed bytes and runs [`hash_bytes`] on `finish`. A real
+/// streaming accumulator would need to carry the 8 running accumulators and
+/// the short/long mode decision across `update` calls, which only pays for
+/// itself if callers routinely hash inputs too large to buffer -- not the
+/// case for single lines or a screenful of text.
+#[derive(Default)]
+pub struct Hasher {
+    buffer: Vec<u8>,
+}
+
+impl Hasher {
+    pub fn new() -> Self {
+        Self { buffer: Vec::new() }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    pub fn finish(&self) -> u64 {
+        hash_bytes(&self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::simd::test::*;
+
+    #[test]
+    fn empty_and_single_byte() {
+        assert_eq!(hash_bytes(b""), hash_bytes(b""));
+        assert_ne!(hash_bytes(b"a"), hash_bytes(b"b"));
+    }
+
+    #[test]
+    fn deterministic_and_sensitive_to_length_buckets() {
+        // Samples from each of the short-path buckets, including their
+        // boundary lengths, plus two from the stripe-loop long path, all
+        // compared for determinism and for avoiding trivial collisions
+        // between samples.
+        let sizes = [0, 1, 3, 8, 16, 17, 64, 128, 129, 240, 241, 1024];
+        let mut seen = Vec::new();
+        for &size in &sizes {
+            let text = generate_random_text(size);
+            let bytes = text.as_bytes();
+            let h1 = hash_bytes(bytes);
+            let h2 = hash_bytes(bytes);
+            assert_eq!(h1, h2, "hash must be deterministic for len={size}");
+            seen.push(h1);
+        }
+        for i in 0..seen.len() {
+            for j in (i + 1)..seen.len() {
+                assert_ne!(seen[i], seen[j], "unexpected collision between bucket samples");
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let text = generate_random_text(513);
+        let bytes = text.as_bytes();
+
+        let mut hasher = Hasher::new();
+        for chunk in bytes.chunks(37) {
+            hasher.update(chunk);
+        }
+
+        assert_eq!(hasher.finish(), hash_bytes(bytes));
+    }
+
+    #[test]
+    fn pseudo_fuzz_avalanche() {
+        // Flipping a single bit anywhere in the input should, overwhelmingly
+        // likely, produce a different hash.
+        let text = generate_random_text(300);
+        let mut bytes = text.into_bytes();
+        let base = hash_bytes(&bytes);
+        let mut rng = make_rng();
+
+        for _ in 0..200 {
+            let idx = rng() % bytes.len();
+            let bit = 1u8 << (rng() % 8);
+            bytes[idx] ^= bit;
+            let flipped = hash_bytes(&bytes);
+            assert_ne!(base, flipped);
+            bytes[idx] ^= bit;
+        }
+    }
+}
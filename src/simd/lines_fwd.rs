@@ -21,7 +21,7 @@ pub fn lines_fwd(
         let beg = haystack.as_ptr();
         let end = beg.add(haystack.len());
         let it = beg.add(offset.min(haystack.len()));
-        let (it, line) = lines_fwd_raw(it, end, line, line_stop);
+        let (it, line) = lines_fwd_raw(it, end, b'\n', line, line_stop);
         (it.offset_from_unsigned(beg), line)
     }
 }
@@ -29,22 +29,24 @@ pub fn lines_fwd(
 unsafe fn lines_fwd_raw(
     beg: *const u8,
     end: *const u8,
+    needle: u8,
     line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
     #[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
-    return unsafe { LINES_FWD_DISPATCH(beg, end, line, line_stop) };
+    return unsafe { LINES_FWD_DISPATCH(beg, end, needle, line, line_stop) };
 
     #[cfg(target_arch = "aarch64")]
-    return unsafe { lines_fwd_neon(beg, end, line, line_stop) };
+    return unsafe { lines_fwd_neon(beg, end, needle, line, line_stop) };
 
     #[allow(unreachable_code)]
-    return unsafe { lines_fwd_fallback(beg, end, line, line_stop) };
+    return unsafe { lines_fwd_fallback(beg, end, needle, line, line_stop) };
 }
 
 unsafe fn lines_fwd_fallback(
     mut beg: *const u8,
     end: *const u8,
+    needle: u8,
     mut line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
@@ -53,7 +55,7 @@ unsafe fn lines_fwd_fallback(
             while !ptr::eq(beg, end) {
                 let c = *beg;
                 beg = beg.add(1);
-                if c == b'\n' {
+                if c == needle {
                     line += 1;
                     if line == line_stop {
                         break;
@@ -69,6 +71,7 @@ unsafe fn lines_fwd_fallback(
 static mut LINES_FWD_DISPATCH: unsafe fn(
     beg: *const u8,
     end: *const u8,
+    needle: u8,
     line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) = lines_fwd_dispatch;
@@ -77,12 +80,13 @@ static mut LINES_FWD_DISPATCH: unsafe fn(
 unsafe fn lines_fwd_dispatch(
     beg: *const u8,
     end: *const u8,
+    needle: u8,
     line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
     let func = if is_x86_feature_detected!("avx2") { lines_fwd_avx2 } else { lines_fwd_fallback };
     unsafe { LINES_FWD_DISPATCH = func };
-    unsafe { func(beg, end, line, line_stop) }
+    unsafe { func(beg, end, needle, line, line_stop) }
 }
 
 #[cfg(target_arch = "x86_64")]
@@ -90,6 +94,7 @@ unsafe fn lines_fwd_dispatch(
 unsafe fn lines_fwd_avx2(
     mut beg: *const u8,
     end: *const u8,
+    needle: u8,
     mut line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
@@ -108,10 +113,10 @@ unsafe fn lines_fwd_avx2(
             }
         }
 
-        let lf = _mm256_set1_epi8(b'\n' as i8);
+        let lf = _mm256_set1_epi8(needle as i8);
         let off = beg.align_offset(32);
         if off != 0 && off < end.offset_from_unsigned(beg) {
-            (beg, line) = lines_fwd_fallback(beg, beg.add(off), line, line_stop);
+            (beg, line) = lines_fwd_fallback(beg, beg.add(off), needle, line, line_stop);
         }
 
         if line < line_stop {
@@ -165,7 +170,7 @@ unsafe fn lines_fwd_avx2(
             }
         }
 
-        lines_fwd_fallback(beg, end, line, line_stop)
+        lines_fwd_fallback(beg, end, needle, line, line_stop)
     }
 }
 
@@ -173,6 +178,7 @@ unsafe fn lines_fwd_avx2(
 unsafe fn lines_fwd_dispatch(
     beg: *const u8,
     end: *const u8,
+    needle: u8,
     line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
@@ -186,7 +192,7 @@ unsafe fn lines_fwd_dispatch(
         lines_fwd_fallback
     };
     unsafe { LINES_FWD_DISPATCH = func };
-    unsafe { func(beg, end, line, line_stop) }
+    unsafe { func(beg, end, needle, line, line_stop) }
 }
 
 #[cfg(target_arch = "loongarch64")]
@@ -194,6 +200,7 @@ unsafe fn lines_fwd_dispatch(
 unsafe fn lines_fwd_lasx(
     mut beg: *const u8,
     end: *const u8,
+    needle: u8,
     mut line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
@@ -214,10 +221,10 @@ unsafe fn lines_fwd_lasx(
             }
         }
 
-        let lf = lasx_xvrepli_b(b'\n' as i32);
+        let lf = lasx_xvrepli_b(needle as i32);
         let off = beg.align_offset(32);
         if off != 0 && off < end.offset_from_unsigned(beg) {
-            (beg, line) = lines_fwd_fallback(beg, beg.add(off), line, line_stop);
+            (beg, line) = lines_fwd_fallback(beg, beg.add(off), needle, line, line_stop);
         }
 
         if line < line_stop {
@@ -260,7 +267,7 @@ unsafe fn lines_fwd_lasx(
             }
         }
 
-        lines_fwd_fallback(beg, end, line, line_stop)
+        lines_fwd_fallback(beg, end, needle, line, line_stop)
     }
 }
 
@@ -269,6 +276,7 @@ unsafe fn lines_fwd_lasx(
 unsafe fn lines_fwd_lsx(
     mut beg: *const u8,
     end: *const u8,
+    needle: u8,
     mut line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
@@ -287,10 +295,10 @@ unsafe fn lines_fwd_lsx(
             }
         }
 
-        let lf = lsx_vrepli_b(b'\n' as i32);
+        let lf = lsx_vrepli_b(needle as i32);
         let off = beg.align_offset(16);
         if off != 0 && off < end.offset_from_unsigned(beg) {
-            (beg, line) = lines_fwd_fallback(beg, beg.add(off), line, line_stop);
+            (beg, line) = lines_fwd_fallback(beg, beg.add(off), needle, line, line_stop);
         }
 
         if line < line_stop {
@@ -333,7 +341,7 @@ unsafe fn lines_fwd_lsx(
             }
         }
 
-        lines_fwd_fallback(beg, end, line, line_stop)
+        lines_fwd_fallback(beg, end, needle, line, line_stop)
     }
 }
 
@@ -341,16 +349,17 @@ unsafe fn lines_fwd_lsx(
 unsafe fn lines_fwd_neon(
     mut beg: *const u8,
     end: *const u8,
+    needle: u8,
     mut line: CoordType,
     line_stop: CoordType,
 ) -> (*const u8, CoordType) {
     unsafe {
         use std::arch::aarch64::*;
 
-        let lf = vdupq_n_u8(b'\n');
+        let lf = vdupq_n_u8(needle);
         let off = beg.align_offset(16);
         if off != 0 && off < end.offset_from_unsigned(beg) {
-            (beg, line) = lines_fwd_fallback(beg, beg.add(off), line, line_stop);
+            (beg, line) = lines_fwd_fallback(beg, beg.add(off), needle, line, line_stop);
         }
 
         if line < line_stop {
@@ -395,7 +404,732 @@ unsafe fn lines_fwd_neon(
             }
         }
 
-        lines_fwd_fallback(beg, end, line, line_stop)
+        lines_fwd_fallback(beg, end, needle, line, line_stop)
+    }
+}
+
+/// The line-ending convention observed by [`lines_fwd_eol`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EolStyle {
+    /// No line terminator was seen in the scanned range.
+    None,
+    /// `\n` only.
+    Lf,
+    /// `\r\n` only.
+    CrLf,
+    /// `\r` only.
+    Cr,
+    /// More than one style was seen.
+    Mixed,
+}
+
+impl EolStyle {
+    fn observe(self, other: EolStyle) -> EolStyle {
+        match (self, other) {
+            (EolStyle::None, other) => other,
+            (this, EolStyle::None) => this,
+            (this, other) if this == other => this,
+            _ => EolStyle::Mixed,
+        }
+    }
+}
+
+/// Like [`lines_fwd`], but treats a `\r` immediately followed by `\n` as a
+/// single logical line terminator, and a standalone `\r` as a terminator
+/// too. Also returns the [`EolStyle`] observed while scanning, so callers
+/// can normalize a mixed-EOL file on save: the one style seen, or
+/// [`EolStyle::Mixed`] if more than one was seen. Styles are not counted,
+/// so a mixed range reports `Mixed`, not its most frequent style.
+///
+/// Unlike [`lines_fwd`], this can't just subtract matches of a single
+/// needle byte: a `\r` only terminates a line on its own if it *isn't*
+/// followed by `\n`, so the vectorized kernels below compute a `\n` mask
+/// and a `\r` mask per block and reconcile them against each other --
+/// shifting the `\n` mask down by one lane to line it up with the `\r`
+/// that might precede it, with a one-bit carry threaded between blocks
+/// for a `\r`/`\n` pair that happens to straddle a block boundary.
+pub fn lines_fwd_eol(
+    haystack: &[u8],
+    offset: usize,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (usize, CoordType, EolStyle) {
+    unsafe {
+        let beg = haystack.as_ptr();
+        let end = beg.add(haystack.len());
+        let it = beg.add(offset.min(haystack.len()));
+        let (it, line, style) = lines_fwd_eol_raw(it, end, line, line_stop);
+        (it.offset_from_unsigned(beg), line, style)
+    }
+}
+
+unsafe fn lines_fwd_eol_raw(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType, EolStyle) {
+    #[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+    return unsafe { LINES_FWD_EOL_DISPATCH(beg, end, line, line_stop) };
+
+    #[cfg(target_arch = "aarch64")]
+    return unsafe { lines_fwd_eol_neon(beg, end, line, line_stop) };
+
+    #[allow(unreachable_code)]
+    return unsafe { lines_fwd_eol_fallback(beg, end, line, line_stop) };
+}
+
+/// Scalar/SWAR-free byte-at-a-time scan, used both as the dispatch
+/// fallback and as the precise tail scan that every vectorized kernel
+/// below hands off to once it can no longer safely commit a whole block
+/// (not enough bytes left, or the block would cross `line_stop`).
+/// Scans byte-at-a-time until either `line_stop` or `stop` is reached.
+///
+/// `stop` and `end` are distinct so this can serve as a *bounded* scan --
+/// e.g. a SIMD kernel's unaligned prefix -- while still correctly pairing a
+/// trailing `\r` landing exactly on `stop` with the `\n` that follows it:
+/// that lookahead byte is only guaranteed to exist below `end`, which for a
+/// bounded scan is further along than `stop` itself. The loop compares
+/// `it < stop` rather than for equality, since consuming such a pair steps
+/// `it` two bytes at once and can leap past `stop` without ever landing on
+/// it exactly.
+unsafe fn lines_fwd_eol_scan(
+    mut it: *const u8,
+    stop: *const u8,
+    end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+    mut style: EolStyle,
+) -> (*const u8, CoordType, EolStyle) {
+    unsafe {
+        while line < line_stop && (it as usize) < (stop as usize) {
+            let c = *it;
+            if c == b'\n' {
+                it = it.add(1);
+                line += 1;
+                style = style.observe(EolStyle::Lf);
+            } else if c == b'\r' {
+                it = it.add(1);
+                if !ptr::eq(it, end) && *it == b'\n' {
+                    it = it.add(1);
+                    style = style.observe(EolStyle::CrLf);
+                } else {
+                    style = style.observe(EolStyle::Cr);
+                }
+                line += 1;
+            } else {
+                it = it.add(1);
+            }
+        }
+
+        (it, line, style)
+    }
+}
+
+unsafe fn lines_fwd_eol_fallback(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType, EolStyle) {
+    unsafe { lines_fwd_eol_scan(beg, end, end, line, line_stop, EolStyle::None) }
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+static mut LINES_FWD_EOL_DISPATCH: unsafe fn(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType, EolStyle) = lines_fwd_eol_dispatch;
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn lines_fwd_eol_dispatch(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType, EolStyle) {
+    let func = if is_x86_feature_detected!("avx2") { lines_fwd_eol_avx2 } else { lines_fwd_eol_fallback };
+    unsafe { LINES_FWD_EOL_DISPATCH = func };
+    unsafe { func(beg, end, line, line_stop) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn lines_fwd_eol_avx2(
+    mut it: *const u8,
+    end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType, EolStyle) {
+    unsafe {
+        use std::arch::x86_64::*;
+
+        let mut style = EolStyle::None;
+
+        let off = it.align_offset(32);
+        if off != 0 && off < end.offset_from_unsigned(it) {
+            (it, line, style) = lines_fwd_eol_scan(it, it.add(off), end, line, line_stop, style);
+        }
+
+        if line < line_stop {
+            let lf = _mm256_set1_epi8(b'\n' as i8);
+            let cr = _mm256_set1_epi8(b'\r' as i8);
+            // Whether the previous block's last byte was a `\r` we haven't
+            // yet been able to classify as standalone or part of a `\r\n`
+            // pair -- that depends on this block's first byte.
+            let mut pending_cr = false;
+
+            while end.offset_from_unsigned(it) >= 32 {
+                let v = _mm256_loadu_si256(it as *const _);
+                let lf_mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(v, lf)) as u32;
+                let cr_mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(v, cr)) as u32;
+
+                if lf_mask == 0 && cr_mask == 0 && !pending_cr {
+                    it = it.add(32);
+                    continue;
+                }
+
+                // The last lane's `\r` (if any) can't be paired within
+                // this block -- its partner, if it exists, is the first
+                // byte of the *next* block -- so it's excluded here and
+                // carried forward instead.
+                let cr_local = cr_mask & 0x7fff_ffff;
+                let crlf_local = cr_local & (lf_mask >> 1);
+                let cr_only_local = cr_local & !crlf_local;
+                let last_byte_is_cr = (cr_mask >> 31) & 1 != 0;
+
+                let carry_is_crlf = pending_cr && (lf_mask & 1) != 0;
+                let carry_count = pending_cr as u32;
+
+                // `\n` lanes that are the second half of a CRLF pair --
+                // whether paired locally or carried in from the previous
+                // block -- must not also be counted as a lone `\n`.
+                let mut consumed_lf = crlf_local << 1;
+                if carry_is_crlf {
+                    consumed_lf |= 1;
+                }
+                let lf_lone = lf_mask & !consumed_lf;
+
+                let new_lines = cr_only_local.count_ones()
+                    + crlf_local.count_ones()
+                    + lf_lone.count_ones()
+                    + carry_count;
+
+                let line_next = line + new_lines as CoordType;
+                if line_next >= line_stop {
+                    break;
+                }
+
+                if pending_cr {
+                    style = style.observe(if carry_is_crlf { EolStyle::CrLf } else { EolStyle::Cr });
+                }
+                if crlf_local != 0 {
+                    style = style.observe(EolStyle::CrLf);
+                }
+                if cr_only_local != 0 {
+                    style = style.observe(EolStyle::Cr);
+                }
+                if lf_lone != 0 {
+                    style = style.observe(EolStyle::Lf);
+                }
+
+                line = line_next;
+                pending_cr = last_byte_is_cr;
+                it = it.add(32);
+            }
+
+            // A trailing `\r` whose pairing we deferred to the next block
+            // is still unresolved -- rewind to it so the scalar tail scan
+            // re-discovers and classifies it itself.
+            if pending_cr {
+                it = it.sub(1);
+            }
+        }
+
+        lines_fwd_eol_scan(it, end, end, line, line_stop, style)
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+unsafe fn lines_fwd_eol_dispatch(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType, EolStyle) {
+    // Unlike `lines_fwd`/`lines_bwd`, this kernel needs a `movemask`-style
+    // per-lane bitmask to reconcile the `\n` and `\r` masks against each
+    // other, and LASX/LSX have no convenient equivalent to fall back on
+    // (see the same tradeoff in `hash::hash_long_dispatch`), so this
+    // always resolves to the scalar scan.
+    unsafe { LINES_FWD_EOL_DISPATCH = lines_fwd_eol_fallback };
+    unsafe { lines_fwd_eol_fallback(beg, end, line, line_stop) }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn lines_fwd_eol_neon(
+    mut it: *const u8,
+    end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType, EolStyle) {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        use crate::simd::memset::neon_movemask;
+
+        let mut style = EolStyle::None;
+
+        if line < line_stop {
+            let lf = vdupq_n_u8(b'\n');
+            let cr = vdupq_n_u8(b'\r');
+            let mut pending_cr = false;
+
+            while end.offset_from_unsigned(it) >= 16 {
+                let v = vld1q_u8(it);
+                let lf_mask = neon_movemask(vceqq_u8(v, lf));
+                let cr_mask = neon_movemask(vceqq_u8(v, cr));
+
+                if lf_mask == 0 && cr_mask == 0 && !pending_cr {
+                    it = it.add(16);
+                    continue;
+                }
+
+                let cr_local = cr_mask & 0x7fff;
+                let crlf_local = cr_local & (lf_mask >> 1);
+                let cr_only_local = cr_local & !crlf_local;
+                let last_byte_is_cr = (cr_mask >> 15) & 1 != 0;
+
+                let carry_is_crlf = pending_cr && (lf_mask & 1) != 0;
+                let carry_count = pending_cr as u32;
+
+                let mut consumed_lf = crlf_local << 1;
+                if carry_is_crlf {
+                    consumed_lf |= 1;
+                }
+                let lf_lone = lf_mask & !consumed_lf;
+
+                let new_lines = cr_only_local.count_ones()
+                    + crlf_local.count_ones()
+                    + lf_lone.count_ones()
+                    + carry_count;
+
+                let line_next = line + new_lines as CoordType;
+                if line_next >= line_stop {
+                    break;
+                }
+
+                if pending_cr {
+                    style = style.observe(if carry_is_crlf { EolStyle::CrLf } else { EolStyle::Cr });
+                }
+                if crlf_local != 0 {
+                    style = style.observe(EolStyle::CrLf);
+                }
+                if cr_only_local != 0 {
+                    style = style.observe(EolStyle::Cr);
+                }
+                if lf_lone != 0 {
+                    style = style.observe(EolStyle::Lf);
+                }
+
+                line = line_next;
+                pending_cr = last_byte_is_cr;
+                it = it.add(16);
+            }
+
+            if pending_cr {
+                it = it.sub(1);
+            }
+        }
+
+        lines_fwd_eol_scan(it, end, end, line, line_stop, style)
+    }
+}
+
+/// Starting from the `offset` in `haystack` with a current line index of
+/// `line`, this seeks backward to the `line_stop`-nth line and returns the
+/// new offset and the line index at that point.
+///
+/// It returns an offset *past* the preceding newline, mirroring
+/// [`lines_fwd`]'s forward-seeking contract. If `line` is already at or
+/// below `line_stop`, it returns immediately.
+pub fn lines_bwd(
+    haystack: &[u8],
+    offset: usize,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (usize, CoordType) {
+    unsafe {
+        let beg = haystack.as_ptr();
+        let end = beg.add(offset.min(haystack.len()));
+        let (it, line) = lines_bwd_raw(beg, end, line, line_stop);
+        (it.offset_from_unsigned(beg), line)
+    }
+}
+
+unsafe fn lines_bwd_raw(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    #[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+    return unsafe { LINES_BWD_DISPATCH(beg, end, line, line_stop) };
+
+    #[cfg(target_arch = "aarch64")]
+    return unsafe { lines_bwd_neon(beg, end, line, line_stop) };
+
+    #[allow(unreachable_code)]
+    return unsafe { lines_bwd_fallback(beg, end, line, line_stop) };
+}
+
+unsafe fn lines_bwd_fallback(
+    beg: *const u8,
+    mut end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    unsafe {
+        if line > line_stop {
+            while !ptr::eq(end, beg) {
+                end = end.sub(1);
+                let c = *end;
+                if c == b'\n' {
+                    line -= 1;
+                    if line == line_stop {
+                        return (end.add(1), line);
+                    }
+                }
+            }
+        }
+        (end, line)
+    }
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "loongarch64"))]
+static mut LINES_BWD_DISPATCH: unsafe fn(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) = lines_bwd_dispatch;
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn lines_bwd_dispatch(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    let func = if is_x86_feature_detected!("avx2") { lines_bwd_avx2 } else { lines_bwd_fallback };
+    unsafe { LINES_BWD_DISPATCH = func };
+    unsafe { func(beg, end, line, line_stop) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn lines_bwd_avx2(
+    beg: *const u8,
+    mut end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    unsafe {
+        use std::arch::x86_64::*;
+
+        #[inline(always)]
+        unsafe fn horizontal_sum_i64(v: __m256i) -> i64 {
+            unsafe {
+                let hi = _mm256_extracti128_si256::<1>(v);
+                let lo = _mm256_castsi256_si128(v);
+                let sum = _mm_add_epi64(lo, hi);
+                let shuf = _mm_shuffle_epi32::<0b11_10_11_10>(sum);
+                let sum = _mm_add_epi64(sum, shuf);
+                _mm_cvtsi128_si64(sum)
+            }
+        }
+
+        let lf = _mm256_set1_epi8(b'\n' as i8);
+        let off = (end as usize) % 32;
+        if off != 0 && off < end.offset_from_unsigned(beg) {
+            (end, line) = lines_bwd_fallback(end.sub(off), end, line, line_stop);
+        }
+
+        if line > line_stop {
+            // Unrolling the loop by 4x speeds things up by >3x, just like lines_fwd_avx2.
+            while end.offset_from_unsigned(beg) >= 128 {
+                let v1 = _mm256_loadu_si256(end.sub(32) as *const _);
+                let v2 = _mm256_loadu_si256(end.sub(64) as *const _);
+                let v3 = _mm256_loadu_si256(end.sub(96) as *const _);
+                let v4 = _mm256_loadu_si256(end.sub(128) as *const _);
+
+                let mut sum = _mm256_setzero_si256();
+                sum = _mm256_sub_epi8(sum, _mm256_cmpeq_epi8(v1, lf));
+                sum = _mm256_sub_epi8(sum, _mm256_cmpeq_epi8(v2, lf));
+                sum = _mm256_sub_epi8(sum, _mm256_cmpeq_epi8(v3, lf));
+                sum = _mm256_sub_epi8(sum, _mm256_cmpeq_epi8(v4, lf));
+
+                let sum = _mm256_sad_epu8(sum, _mm256_setzero_si256());
+                let sum = horizontal_sum_i64(sum);
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(128);
+                line = line_next;
+            }
+
+            while end.offset_from_unsigned(beg) >= 32 {
+                let v = _mm256_loadu_si256(end.sub(32) as *const _);
+                let c = _mm256_cmpeq_epi8(v, lf);
+
+                let ones = _mm256_and_si256(c, _mm256_set1_epi8(0x01));
+                let sum = _mm256_sad_epu8(ones, _mm256_setzero_si256());
+                let sum = horizontal_sum_i64(sum);
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(32);
+                line = line_next;
+            }
+        }
+
+        lines_bwd_fallback(beg, end, line, line_stop)
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+unsafe fn lines_bwd_dispatch(
+    beg: *const u8,
+    end: *const u8,
+    line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    use std::arch::is_loongarch_feature_detected;
+
+    let func = if is_loongarch_feature_detected!("lasx") {
+        lines_bwd_lasx
+    } else if is_loongarch_feature_detected!("lsx") {
+        lines_bwd_lsx
+    } else {
+        lines_bwd_fallback
+    };
+    unsafe { LINES_BWD_DISPATCH = func };
+    unsafe { func(beg, end, line, line_stop) }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lasx")]
+unsafe fn lines_bwd_lasx(
+    beg: *const u8,
+    mut end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    unsafe {
+        use std::arch::loongarch64::*;
+        use std::mem::transmute as T;
+
+        #[inline(always)]
+        unsafe fn horizontal_sum(sum: v32i8) -> u32 {
+            unsafe {
+                let sum = lasx_xvhaddw_h_b(sum, sum);
+                let sum = lasx_xvhaddw_w_h(sum, sum);
+                let sum = lasx_xvhaddw_d_w(sum, sum);
+                let sum = lasx_xvhaddw_q_d(sum, sum);
+                let tmp = lasx_xvpermi_q::<1>(T(sum), T(sum));
+                let sum = lasx_xvadd_w(T(sum), T(tmp));
+                lasx_xvpickve2gr_wu::<0>(sum)
+            }
+        }
+
+        let lf = lasx_xvrepli_b(b'\n' as i32);
+        let off = (end as usize) % 32;
+        if off != 0 && off < end.offset_from_unsigned(beg) {
+            (end, line) = lines_bwd_fallback(end.sub(off), end, line, line_stop);
+        }
+
+        if line > line_stop {
+            while end.offset_from_unsigned(beg) >= 128 {
+                let v1 = lasx_xvld::<-32>(end as *const _);
+                let v2 = lasx_xvld::<-64>(end as *const _);
+                let v3 = lasx_xvld::<-96>(end as *const _);
+                let v4 = lasx_xvld::<-128>(end as *const _);
+
+                let mut sum = lasx_xvrepli_b(0);
+                sum = lasx_xvsub_b(sum, lasx_xvseq_b(v1, lf));
+                sum = lasx_xvsub_b(sum, lasx_xvseq_b(v2, lf));
+                sum = lasx_xvsub_b(sum, lasx_xvseq_b(v3, lf));
+                sum = lasx_xvsub_b(sum, lasx_xvseq_b(v4, lf));
+                let sum = horizontal_sum(sum);
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(128);
+                line = line_next;
+            }
+
+            while end.offset_from_unsigned(beg) >= 32 {
+                let v = lasx_xvld::<-32>(end as *const _);
+                let c = lasx_xvseq_b(v, lf);
+
+                let ones = lasx_xvand_v(T(c), T(lasx_xvrepli_b(1)));
+                let sum = horizontal_sum(T(ones));
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(32);
+                line = line_next;
+            }
+        }
+
+        lines_bwd_fallback(beg, end, line, line_stop)
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lsx")]
+unsafe fn lines_bwd_lsx(
+    beg: *const u8,
+    mut end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    unsafe {
+        use std::arch::loongarch64::*;
+        use std::mem::transmute as T;
+
+        #[inline(always)]
+        unsafe fn horizontal_sum(sum: v16i8) -> u32 {
+            unsafe {
+                let sum = lsx_vhaddw_h_b(sum, sum);
+                let sum = lsx_vhaddw_w_h(sum, sum);
+                let sum = lsx_vhaddw_d_w(sum, sum);
+                let sum = lsx_vhaddw_q_d(sum, sum);
+                lsx_vpickve2gr_wu::<0>(T(sum))
+            }
+        }
+
+        let lf = lsx_vrepli_b(b'\n' as i32);
+        let off = (end as usize) % 16;
+        if off != 0 && off < end.offset_from_unsigned(beg) {
+            (end, line) = lines_bwd_fallback(end.sub(off), end, line, line_stop);
+        }
+
+        if line > line_stop {
+            while end.offset_from_unsigned(beg) >= 64 {
+                let v1 = lsx_vld::<-16>(end as *const _);
+                let v2 = lsx_vld::<-32>(end as *const _);
+                let v3 = lsx_vld::<-48>(end as *const _);
+                let v4 = lsx_vld::<-64>(end as *const _);
+
+                let mut sum = lsx_vrepli_b(0);
+                sum = lsx_vsub_b(sum, lsx_vseq_b(v1, lf));
+                sum = lsx_vsub_b(sum, lsx_vseq_b(v2, lf));
+                sum = lsx_vsub_b(sum, lsx_vseq_b(v3, lf));
+                sum = lsx_vsub_b(sum, lsx_vseq_b(v4, lf));
+                let sum = horizontal_sum(sum);
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(64);
+                line = line_next;
+            }
+
+            while end.offset_from_unsigned(beg) >= 16 {
+                let v = lsx_vld::<-16>(end as *const _);
+                let c = lsx_vseq_b(v, lf);
+
+                let ones = lsx_vand_v(T(c), T(lsx_vrepli_b(1)));
+                let sum = horizontal_sum(T(ones));
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(16);
+                line = line_next;
+            }
+        }
+
+        lines_bwd_fallback(beg, end, line, line_stop)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn lines_bwd_neon(
+    beg: *const u8,
+    mut end: *const u8,
+    mut line: CoordType,
+    line_stop: CoordType,
+) -> (*const u8, CoordType) {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        let lf = vdupq_n_u8(b'\n');
+        let off = (end as usize) % 16;
+        if off != 0 && off < end.offset_from_unsigned(beg) {
+            (end, line) = lines_bwd_fallback(end.sub(off), end, line, line_stop);
+        }
+
+        if line > line_stop {
+            while end.offset_from_unsigned(beg) >= 64 {
+                let v1 = vld1q_u8(end.sub(16));
+                let v2 = vld1q_u8(end.sub(32));
+                let v3 = vld1q_u8(end.sub(48));
+                let v4 = vld1q_u8(end.sub(64));
+
+                let mut sum = vdupq_n_u8(0);
+                sum = vsubq_u8(sum, vceqq_u8(v1, lf));
+                sum = vsubq_u8(sum, vceqq_u8(v2, lf));
+                sum = vsubq_u8(sum, vceqq_u8(v3, lf));
+                sum = vsubq_u8(sum, vceqq_u8(v4, lf));
+
+                let sum = vaddvq_u8(sum);
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(64);
+                line = line_next;
+            }
+
+            while end.offset_from_unsigned(beg) >= 16 {
+                let v = vld1q_u8(end.sub(16));
+                let c = vceqq_u8(v, lf);
+                let c = vandq_u8(c, vdupq_n_u8(0x01));
+                let sum = vaddvq_u8(c);
+
+                let line_next = line - sum as CoordType;
+                if line_next <= line_stop {
+                    break;
+                }
+
+                end = end.sub(16);
+                line = line_next;
+            }
+        }
+
+        lines_bwd_fallback(beg, end, line, line_stop)
     }
 }
 
@@ -448,4 +1182,121 @@ mod test {
         }
         (offset, line)
     }
+
+    #[test]
+    fn pseudo_fuzz_bwd() {
+        let text = generate_random_text(1024);
+        let lines = count_lines(&text);
+        let mut offset_rng = make_rng();
+        let mut line_rng = make_rng();
+        let mut line_distance_rng = make_rng();
+
+        for _ in 0..1000 {
+            let offset = offset_rng() % (text.len() + 1);
+            let line = line_rng() % 100;
+            let line_stop = line.saturating_sub(line_distance_rng() % (lines + 1));
+
+            let line = line as CoordType;
+            let line_stop = line_stop as CoordType;
+
+            let expected = reference_lines_bwd(text.as_bytes(), offset, line, line_stop);
+            let actual = lines_bwd(text.as_bytes(), offset, line, line_stop);
+
+            assert_eq!(expected, actual);
+        }
+    }
+
+    fn reference_lines_bwd(
+        haystack: &[u8],
+        offset: usize,
+        mut line: CoordType,
+        line_stop: CoordType,
+    ) -> (usize, CoordType) {
+        let mut offset = offset;
+        if line > line_stop {
+            while offset > 0 {
+                offset -= 1;
+                let c = haystack[offset];
+                if c == b'\n' {
+                    line -= 1;
+                    if line == line_stop {
+                        return (offset + 1, line);
+                    }
+                }
+            }
+        }
+        (offset, line)
+    }
+
+    #[test]
+    fn pseudo_fuzz_eol() {
+        let base = generate_random_text(256);
+        let mut mix_rng = make_rng();
+        let mut text = Vec::new();
+        for b in base.bytes() {
+            if b == b'\n' {
+                match mix_rng() % 3 {
+                    0 => text.push(b'\n'),
+                    1 => {
+                        text.push(b'\r');
+                        text.push(b'\n');
+                    }
+                    _ => text.push(b'\r'),
+                }
+            } else {
+                text.push(b);
+            }
+        }
+
+        let lines = reference_lines_fwd_eol(&text, 0, 0, CoordType::MAX).1;
+        let mut offset_rng = make_rng();
+        let mut line_rng = make_rng();
+        let mut line_distance_rng = make_rng();
+
+        for _ in 0..1000 {
+            let offset = offset_rng() % (text.len() + 1);
+            let line = (line_rng() % 100) as CoordType;
+            let line_stop =
+                (line + (line_distance_rng() % (lines as usize + 1)) as CoordType).saturating_sub(5);
+
+            let expected = reference_lines_fwd_eol(&text, offset, line, line_stop);
+            let actual = lines_fwd_eol(&text, offset, line, line_stop);
+
+            assert_eq!(expected, actual);
+        }
+    }
+
+    fn reference_lines_fwd_eol(
+        haystack: &[u8],
+        mut offset: usize,
+        mut line: CoordType,
+        line_stop: CoordType,
+    ) -> (usize, CoordType, EolStyle) {
+        let mut style = EolStyle::None;
+        if line < line_stop {
+            while offset < haystack.len() {
+                let c = haystack[offset];
+                offset += 1;
+                if c == b'\r' {
+                    if offset < haystack.len() && haystack[offset] == b'\n' {
+                        offset += 1;
+                        style = style.observe(EolStyle::CrLf);
+                    } else {
+                        style = style.observe(EolStyle::Cr);
+                    }
+                    line += 1;
+                    if line == line_stop {
+                        break;
+                    }
+                } else if c == b'\n' {
+                    style = style.observe(EolStyle::Lf);
+                    line += 1;
+                    if line == line_stop {
+                        break;
+                    }
+                }
+            }
+        }
+        (offset, line, style)
+    }
 }
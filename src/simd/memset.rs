@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-//! `memchr` for arbitrary sizes (1/2/4/8 bytes).
+//! `memset`/`memchr`/`memrchr` for arbitrary sizes (1/2/4/8 bytes).
 //!
 //! Clang calls the C `memset` function only for byte-sized types (or 0 fills).
 //! We however need to fill other types as well. For that, clang generates
@@ -12,8 +12,15 @@
 //! 4 sizes: By duplicating smaller types into a larger `u64` register we can
 //! treat all sizes as if they were `u64`. The only thing we need to take care
 //! of is the tail end of the array, which needs to write 0-7 additional bytes.
+//!
+//! `memchr`/`memrchr` reuse the same trick in reverse: the needle is
+//! broadcast into a `u64` the same way a fill value is, and a word is
+//! scanned for a match via the classic zero-detection expression
+//! (`(x - ones) & !x & high_bits`), rather than writing to it.
 
+use std::cmp::Ordering;
 use std::mem;
+use std::ptr;
 
 /// A marker trait for types that are safe to `memset`.
 ///
@@ -70,6 +77,50 @@ pub fn memset<T: MemsetSafe>(dst: &mut [T], val: T) {
     }
 }
 
+/// Fills a fixed-size array with the given value.
+///
+/// Unlike [`memset`], the total byte size (`N * size_of::<T>()`) is known at
+/// compile time. For the small stack buffers and struct-like cell arrays
+/// this editor clears constantly, that means the compiler can inline this
+/// down to a couple of overlapping stores with zero branches, rather than
+/// going through the `MEMSET_DISPATCH` function pointer -- an indirection
+/// the optimizer can't see through even when the length is known.
+#[inline]
+pub fn memset_array<T: MemsetSafe, const N: usize>(dst: &mut [T; N], val: T) {
+    let len = N * mem::size_of::<T>();
+    let beg = dst.as_mut_ptr() as *mut u8;
+
+    if len <= 16 {
+        let val = broadcast_needle(val);
+        unsafe { memset_short(beg, len, val) };
+    } else {
+        memset(dst.as_mut_slice(), val);
+    }
+}
+
+/// Writes `len` (0-16) bytes of the lane-duplicated `val` to `beg`, using
+/// the same overlapping head/tail trick as `memset_fallback`'s tail case.
+/// Mirrors `short_write`-style const-generic specializations: with `len`
+/// known at the call site, this collapses to a single branch-free pair of
+/// stores after inlining.
+#[inline(always)]
+unsafe fn memset_short(beg: *mut u8, len: usize, val: u64) {
+    unsafe {
+        if len >= 8 {
+            (beg as *mut u64).write_unaligned(val);
+            (beg.add(len - 8) as *mut u64).write_unaligned(val);
+        } else if len >= 4 {
+            (beg as *mut u32).write_unaligned(val as u32);
+            (beg.add(len - 4) as *mut u32).write_unaligned(val as u32);
+        } else if len >= 2 {
+            (beg as *mut u16).write_unaligned(val as u16);
+            (beg.add(len - 2) as *mut u16).write_unaligned(val as u16);
+        } else if len >= 1 {
+            beg.write(val as u8);
+        }
+    }
+}
+
 #[inline]
 fn memset_raw(beg: *mut u8, end: *mut u8, val: u64) {
     #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "loongarch64"))]
@@ -78,6 +129,9 @@ fn memset_raw(beg: *mut u8, end: *mut u8, val: u64) {
     #[cfg(target_arch = "aarch64")]
     return unsafe { memset_neon(beg, end, val) };
 
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    return unsafe { memset_simd128(beg, end, val) };
+
     #[allow(unreachable_code)]
     return unsafe { memset_fallback(beg, end, val) };
 }
@@ -405,92 +459,1376 @@ unsafe fn memset_neon(mut beg: *mut u8, end: *mut u8, val: u64) {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::fmt;
-    use std::ops::Not;
+// On wasm32 without wasi there's no libc to provide `memset`, so without a
+// vectorized path here these SWAR loops would fall all the way through to
+// `memset_fallback`. `v128` stores keep fills fast in browser/edge builds
+// where the editor core is compiled to WebAssembly.
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+unsafe fn memset_simd128(mut beg: *mut u8, end: *mut u8, val: u64) {
+    unsafe {
+        use std::arch::wasm32::*;
 
-    use super::*;
+        let mut remaining = end.offset_from_unsigned(beg);
 
-    fn check_memset<T>(val: T, len: usize)
-    where
-        T: MemsetSafe + Not<Output = T> + PartialEq + fmt::Debug,
-    {
-        let mut buf = vec![!val; len];
-        memset(&mut buf, val);
-        assert!(buf.iter().all(|&x| x == val));
+        if remaining >= 16 {
+            let fill = u64x2_splat(val);
+
+            while remaining >= 32 {
+                v128_store(beg as *mut _, fill);
+                v128_store(beg.add(16) as *mut _, fill);
+
+                beg = beg.add(32);
+                remaining -= 32;
+            }
+
+            if remaining >= 16 {
+                // 16-31 bytes remaining
+                v128_store(beg as *mut _, fill);
+                v128_store(end.sub(16) as *mut _, fill);
+                return;
+            }
+        }
+
+        if remaining >= 8 {
+            // 8-15 bytes remaining
+            (beg as *mut u64).write_unaligned(val);
+            (end.sub(8) as *mut u64).write_unaligned(val);
+        } else if remaining >= 4 {
+            // 4-7 bytes remaining
+            (beg as *mut u32).write_unaligned(val as u32);
+            (end.sub(4) as *mut u32).write_unaligned(val as u32);
+        } else if remaining >= 2 {
+            // 2-3 bytes remaining
+            (beg as *mut u16).write_unaligned(val as u16);
+            (end.sub(2) as *mut u16).write_unaligned(val as u16);
+        } else if remaining >= 1 {
+            // 1 byte remaining
+            beg.write(val as u8);
+        }
     }
+}
 
-    #[test]
-    fn test_memset_empty() {
-        check_memset(0u8, 0);
-        check_memset(0u16, 0);
-        check_memset(0u32, 0);
-        check_memset(0u64, 0);
+/// Returns the index of the first element in `haystack` equal to `needle`,
+/// or `None` if it doesn't occur.
+#[inline]
+pub fn memchr<T: MemsetSafe + PartialEq>(haystack: &[T], needle: T) -> Option<usize> {
+    unsafe {
+        let size = mem::size_of::<T>();
+        let beg = haystack.as_ptr() as *const u8;
+        let end = beg.add(haystack.len() * size);
+        let val = broadcast_needle(needle);
+        memchr_raw(beg, end, val, size).map(|p| p.offset_from_unsigned(beg) / size)
     }
+}
 
-    #[test]
-    fn test_memset_single() {
-        check_memset(0u8, 1);
-        check_memset(0xFFu8, 1);
-        check_memset(0xABu16, 1);
-        check_memset(0x12345678u32, 1);
-        check_memset(0xDEADBEEFu64, 1);
+/// Returns the index of the last element in `haystack` equal to `needle`,
+/// or `None` if it doesn't occur.
+#[inline]
+pub fn memrchr<T: MemsetSafe + PartialEq>(haystack: &[T], needle: T) -> Option<usize> {
+    unsafe {
+        let size = mem::size_of::<T>();
+        let beg = haystack.as_ptr() as *const u8;
+        let end = beg.add(haystack.len() * size);
+        let val = broadcast_needle(needle);
+        memrchr_raw(beg, end, val, size).map(|p| p.offset_from_unsigned(beg) / size)
     }
+}
 
-    #[test]
-    fn test_memset_small() {
-        for &len in &[2, 3, 4, 5, 7, 8, 9] {
-            check_memset(0xAAu8, len);
-            check_memset(0xBEEFu16, len);
-            check_memset(0xCAFEBABEu32, len);
-            check_memset(0x1234567890ABCDEFu64, len);
+/// Duplicates `needle` across all lanes of a `u64`, the same way `memset`
+/// turns its fill value into a lane-duplicated `u64`.
+#[inline]
+fn broadcast_needle<T: MemsetSafe>(needle: T) -> u64 {
+    unsafe {
+        match mem::size_of::<T>() {
+            1 => mem::transmute_copy::<_, u8>(&needle) as u64 * 0x0101010101010101,
+            2 => mem::transmute_copy::<_, u16>(&needle) as u64 * 0x0001000100010001,
+            4 => mem::transmute_copy::<_, u32>(&needle) as u64 * 0x0000000100000001,
+            8 => mem::transmute_copy::<_, u64>(&needle),
+            _ => unreachable!(),
         }
     }
+}
 
-    #[test]
-    fn test_memset_large() {
-        check_memset(0u8, 1000);
-        check_memset(0xFFu8, 1024);
-        check_memset(0xBEEFu16, 512);
-        check_memset(0xCAFEBABEu32, 256);
-        check_memset(0x1234567890ABCDEFu64, 128);
+/// Finds the first lane-aligned match of `val` (already broadcast via
+/// [`broadcast_needle`]) in `[beg, end)`, which must be a multiple of `size`
+/// bytes long. Returns a pointer to one of the matching element's bytes
+/// (not necessarily its first), so the element index is the byte offset
+/// divided by `size`.
+#[inline]
+fn memchr_raw(beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "loongarch64"))]
+    return unsafe { MEMCHR_DISPATCH(beg, end, val, size) };
+
+    #[cfg(target_arch = "aarch64")]
+    return unsafe { memchr_neon(beg, end, val, size) };
+
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    return unsafe { memchr_simd128(beg, end, val, size) };
+
+    #[allow(unreachable_code)]
+    return unsafe { memchr_fallback(beg, end, val, size) };
+}
+
+/// Mirror of [`memchr_raw`] that scans from `end` toward `beg`.
+#[inline]
+fn memrchr_raw(beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "loongarch64"))]
+    return unsafe { MEMRCHR_DISPATCH(beg, end, val, size) };
+
+    #[cfg(target_arch = "aarch64")]
+    return unsafe { memrchr_neon(beg, end, val, size) };
+
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    return unsafe { memrchr_simd128(beg, end, val, size) };
+
+    #[allow(unreachable_code)]
+    return unsafe { memrchr_fallback(beg, end, val, size) };
+}
+
+/// Masks used by the classic SWAR zero-detection trick, keyed by lane size
+/// in bytes. A lane is a match iff
+/// `(x.wrapping_sub(ones) & !x & high_bits) != 0`, where `x` is the haystack
+/// word XORed with the broadcast needle.
+const fn swar_masks(size: usize) -> (u64, u64) {
+    match size {
+        1 => (0x0101010101010101, 0x8080808080808080),
+        2 => (0x0001000100010001, 0x8000800080008000),
+        4 => (0x0000000100000001, 0x8000000080000000),
+        8 => (0x0000000000000001, 0x8000000000000000),
+        _ => unreachable!(),
     }
+}
 
-    #[test]
-    fn test_memset_various_values() {
-        check_memset(0u8, 17);
-        check_memset(0x7Fu8, 17);
-        check_memset(0x8001u16, 17);
-        check_memset(0xFFFFFFFFu32, 17);
-        check_memset(0x8000000000000001u64, 17);
+/// Scalar/SWAR fallback shared by every architecture. Processes the buffer
+/// 8 bytes at a time; the lowest set bit in the zero-detection expression
+/// is the top bit of the first matching lane, so dividing it by 8 gives the
+/// offset of that lane's last byte within the word.
+#[inline(never)]
+unsafe fn memchr_fallback(mut beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        let (ones, high) = swar_masks(size);
+        let mut remaining = end.offset_from_unsigned(beg);
+
+        while remaining >= 8 {
+            let w = (beg as *const u64).read_unaligned();
+            let x = w ^ val;
+            let matches = x.wrapping_sub(ones) & !x & high;
+            if matches != 0 {
+                return Some(beg.add((matches.trailing_zeros() / 8) as usize));
+            }
+            beg = beg.add(8);
+            remaining -= 8;
+        }
+
+        while remaining >= size {
+            if lane_eq(beg, val, size) {
+                return Some(beg);
+            }
+            beg = beg.add(size);
+            remaining -= size;
+        }
+
+        None
     }
+}
 
-    #[test]
-    fn test_memset_signed_types() {
-        check_memset(-1i8, 8);
-        check_memset(-2i16, 8);
-        check_memset(-3i32, 8);
-        check_memset(-4i64, 8);
-        check_memset(-5isize, 8);
+/// Mirror of [`memchr_fallback`], scanning from `end` toward `beg`.
+#[inline(never)]
+unsafe fn memrchr_fallback(beg: *const u8, mut end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        let (ones, high) = swar_masks(size);
+        let mut remaining = end.offset_from_unsigned(beg);
+
+        while remaining >= 8 {
+            let word = end.sub(8);
+            let w = (word as *const u64).read_unaligned();
+            let x = w ^ val;
+            let mut matches = x.wrapping_sub(ones) & !x & high;
+            while matches != 0 {
+                // `leading_zeros` counts from the most significant (i.e.
+                // last) byte, which is the match we want for a reverse scan.
+                // Unlike a forward scan's `trailing_zeros` -- always exact,
+                // since a zero lane can only ever borrow into a *higher*
+                // lane -- the highest flagged lane here can be a spurious
+                // borrow artifact of a genuine zero lane just below it, so
+                // it must be verified before trusting it; if it's spurious,
+                // clear just that flag bit and look at the next-highest one.
+                let flag_byte = 7 - (matches.leading_zeros() / 8) as usize;
+                let byte = flag_byte + 1 - size;
+                if lane_eq(word.add(byte), val, size) {
+                    return Some(word.add(byte));
+                }
+                matches &= !(1u64 << (flag_byte * 8 + 7));
+            }
+            end = word;
+            remaining -= 8;
+        }
+
+        while remaining >= size {
+            end = end.sub(size);
+            if lane_eq(end, val, size) {
+                return Some(end);
+            }
+            remaining -= size;
+        }
+
+        None
     }
+}
 
-    #[test]
-    fn test_memset_usize_isize() {
-        check_memset(0usize, 4);
-        check_memset(usize::MAX, 4);
-        check_memset(0isize, 4);
-        check_memset(isize::MIN, 4);
+/// Compares a single `size`-byte lane at `p` against the low bytes of the
+/// broadcast needle `val`.
+#[inline(always)]
+unsafe fn lane_eq(p: *const u8, val: u64, size: usize) -> bool {
+    unsafe {
+        match size {
+            1 => *p == val as u8,
+            2 => (p as *const u16).read_unaligned() == val as u16,
+            4 => (p as *const u32).read_unaligned() == val as u32,
+            8 => (p as *const u64).read_unaligned() == val,
+            _ => unreachable!(),
+        }
     }
+}
 
-    #[test]
-    fn test_memset_alignment() {
-        // Check that memset works for slices not aligned to 8 bytes
-        let mut buf = [0u8; 15];
-        for offset in 0..8 {
-            let slice = &mut buf[offset..(offset + 7)];
-            memset(slice, 0x5A);
-            assert!(slice.iter().all(|&x| x == 0x5A));
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "loongarch64"))]
+static mut MEMCHR_DISPATCH: unsafe fn(
+    beg: *const u8,
+    end: *const u8,
+    val: u64,
+    size: usize,
+) -> Option<*const u8> = memchr_dispatch;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "loongarch64"))]
+static mut MEMRCHR_DISPATCH: unsafe fn(
+    beg: *const u8,
+    end: *const u8,
+    val: u64,
+    size: usize,
+) -> Option<*const u8> = memrchr_dispatch;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn memchr_dispatch(beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    let func = if is_x86_feature_detected!("avx2") { memchr_avx2 } else { memchr_fallback };
+    unsafe { MEMCHR_DISPATCH = func };
+    unsafe { func(beg, end, val, size) }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn memrchr_dispatch(beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    let func = if is_x86_feature_detected!("avx2") { memrchr_avx2 } else { memrchr_fallback };
+    unsafe { MEMRCHR_DISPATCH = func };
+    unsafe { func(beg, end, val, size) }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn memchr_avx2(mut beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::*;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::*;
+
+        // `pcmpeqq` (64-bit lanes) is SSE4.1, not guaranteed by "avx2" alone
+        // on every target-feature set we build for, so 8-byte needles fall
+        // back to the SWAR scanner, which is still plenty fast for a
+        // single-word comparison.
+        if size == 8 {
+            return memchr_fallback(beg, end, val, size);
+        }
+
+        let needle = _mm256_set1_epi64x(val as i64);
+
+        while end.offset_from_unsigned(beg) >= 32 {
+            let v = _mm256_loadu_si256(beg as *const _);
+            let eq = match size {
+                1 => _mm256_cmpeq_epi8(v, needle),
+                2 => _mm256_cmpeq_epi16(v, needle),
+                4 => _mm256_cmpeq_epi32(v, needle),
+                _ => unreachable!(),
+            };
+            let mask = _mm256_movemask_epi8(eq) as u32;
+            if mask != 0 {
+                let offset = mask.trailing_zeros() as usize;
+                return Some(beg.add(offset - offset % size));
+            }
+            beg = beg.add(32);
+        }
+
+        memchr_fallback(beg, end, val, size)
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn memrchr_avx2(beg: *const u8, mut end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::*;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::*;
+
+        if size == 8 {
+            return memrchr_fallback(beg, end, val, size);
+        }
+
+        let needle = _mm256_set1_epi64x(val as i64);
+
+        while end.offset_from_unsigned(beg) >= 32 {
+            end = end.sub(32);
+            let v = _mm256_loadu_si256(end as *const _);
+            let eq = match size {
+                1 => _mm256_cmpeq_epi8(v, needle),
+                2 => _mm256_cmpeq_epi16(v, needle),
+                4 => _mm256_cmpeq_epi32(v, needle),
+                _ => unreachable!(),
+            };
+            let mask = _mm256_movemask_epi8(eq) as u32;
+            if mask != 0 {
+                let offset = 31 - mask.leading_zeros() as usize;
+                return Some(end.add(offset - offset % size));
+            }
+        }
+
+        memrchr_fallback(beg, end, val, size)
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+fn memchr_dispatch(beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    use std::arch::is_loongarch_feature_detected;
+    let func = if is_loongarch_feature_detected!("lasx") { memchr_lasx } else { memchr_fallback };
+    unsafe { MEMCHR_DISPATCH = func };
+    unsafe { func(beg, end, val, size) }
+}
+
+#[cfg(target_arch = "loongarch64")]
+fn memrchr_dispatch(beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    use std::arch::is_loongarch_feature_detected;
+    let func = if is_loongarch_feature_detected!("lasx") { memrchr_lasx } else { memrchr_fallback };
+    unsafe { MEMRCHR_DISPATCH = func };
+    unsafe { func(beg, end, val, size) }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lasx")]
+unsafe fn memchr_lasx(mut beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        use std::arch::loongarch64::*;
+        use std::mem::transmute as T;
+
+        let needle: v32i8 = T(lasx_xvreplgr2vr_d(val as i64));
+
+        while end.offset_from_unsigned(beg) >= 32 {
+            let v = lasx_xvld::<0>(beg as *const _);
+            let eq: v32i8 = match size {
+                1 => lasx_xvseq_b(v, needle),
+                2 => T(lasx_xvseq_h(T(v), T(needle))),
+                4 => T(lasx_xvseq_w(T(v), T(needle))),
+                8 => T(lasx_xvseq_d(T(v), T(needle))),
+                _ => unreachable!(),
+            };
+            // `xvmskltz.b` writes one 16-bit mask per 128-bit half (words 0 and 4).
+            let mask: v8i32 = T(lasx_xvmskltz_b(eq));
+            let mask = lasx_xvpickve2gr_wu::<0>(mask) | (lasx_xvpickve2gr_wu::<4>(mask) << 16);
+            if mask != 0 {
+                let offset = mask.trailing_zeros() as usize;
+                return Some(beg.add(offset - offset % size));
+            }
+            beg = beg.add(32);
+        }
+
+        memchr_fallback(beg, end, val, size)
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lasx")]
+unsafe fn memrchr_lasx(beg: *const u8, mut end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        use std::arch::loongarch64::*;
+        use std::mem::transmute as T;
+
+        let needle: v32i8 = T(lasx_xvreplgr2vr_d(val as i64));
+
+        while end.offset_from_unsigned(beg) >= 32 {
+            end = end.sub(32);
+            let v = lasx_xvld::<0>(end as *const _);
+            let eq: v32i8 = match size {
+                1 => lasx_xvseq_b(v, needle),
+                2 => T(lasx_xvseq_h(T(v), T(needle))),
+                4 => T(lasx_xvseq_w(T(v), T(needle))),
+                8 => T(lasx_xvseq_d(T(v), T(needle))),
+                _ => unreachable!(),
+            };
+            let mask: v8i32 = T(lasx_xvmskltz_b(eq));
+            let mask = lasx_xvpickve2gr_wu::<0>(mask) | (lasx_xvpickve2gr_wu::<4>(mask) << 16);
+            if mask != 0 {
+                let offset = 31 - mask.leading_zeros() as usize;
+                return Some(end.add(offset - offset % size));
+            }
         }
+
+        memrchr_fallback(beg, end, val, size)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn memchr_neon(mut beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        let needle = vreinterpretq_u8_u64(vdupq_n_u64(val));
+
+        while end.offset_from_unsigned(beg) >= 16 {
+            let v = vld1q_u8(beg);
+            let eq = match size {
+                1 => vceqq_u8(v, needle),
+                2 => vreinterpretq_u8_u16(vceqq_u16(vreinterpretq_u16_u8(v), vreinterpretq_u16_u8(needle))),
+                4 => vreinterpretq_u8_u32(vceqq_u32(vreinterpretq_u32_u8(v), vreinterpretq_u32_u8(needle))),
+                8 => vreinterpretq_u8_u64(vceqq_u64(vreinterpretq_u64_u8(v), vreinterpretq_u64_u8(needle))),
+                _ => unreachable!(),
+            };
+            // NEON has no `movemask`; `neon_movemask` builds the 16-bit
+            // mask from the comparison result instead.
+            let mask = neon_movemask(eq);
+            if mask != 0 {
+                let offset = mask.trailing_zeros() as usize;
+                return Some(beg.add(offset - offset % size));
+            }
+            beg = beg.add(16);
+        }
+
+        memchr_fallback(beg, end, val, size)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn memrchr_neon(beg: *const u8, mut end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        let needle = vreinterpretq_u8_u64(vdupq_n_u64(val));
+
+        while end.offset_from_unsigned(beg) >= 16 {
+            end = end.sub(16);
+            let v = vld1q_u8(end);
+            let eq = match size {
+                1 => vceqq_u8(v, needle),
+                2 => vreinterpretq_u8_u16(vceqq_u16(vreinterpretq_u16_u8(v), vreinterpretq_u16_u8(needle))),
+                4 => vreinterpretq_u8_u32(vceqq_u32(vreinterpretq_u32_u8(v), vreinterpretq_u32_u8(needle))),
+                8 => vreinterpretq_u8_u64(vceqq_u64(vreinterpretq_u64_u8(v), vreinterpretq_u64_u8(needle))),
+                _ => unreachable!(),
+            };
+            let mask = neon_movemask(eq);
+            if mask != 0 {
+                let offset = 15 - mask.leading_zeros() as usize;
+                return Some(end.add(offset - offset % size));
+            }
+        }
+
+        memrchr_fallback(beg, end, val, size)
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+unsafe fn memchr_simd128(mut beg: *const u8, end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        use std::arch::wasm32::*;
+
+        let needle = u64x2_splat(val);
+
+        while end.offset_from_unsigned(beg) >= 16 {
+            let v = v128_load(beg as *const _);
+            let eq = match size {
+                1 => u8x16_eq(v, needle),
+                2 => u16x8_eq(v, needle),
+                4 => u32x4_eq(v, needle),
+                8 => i64x2_eq(v, needle),
+                _ => unreachable!(),
+            };
+            let mask = u8x16_bitmask(eq) as u32;
+            if mask != 0 {
+                let offset = mask.trailing_zeros() as usize;
+                return Some(beg.add(offset - offset % size));
+            }
+            beg = beg.add(16);
+        }
+
+        memchr_fallback(beg, end, val, size)
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+unsafe fn memrchr_simd128(beg: *const u8, mut end: *const u8, val: u64, size: usize) -> Option<*const u8> {
+    unsafe {
+        use std::arch::wasm32::*;
+
+        let needle = u64x2_splat(val);
+
+        while end.offset_from_unsigned(beg) >= 16 {
+            end = end.sub(16);
+            let v = v128_load(end as *const _);
+            let eq = match size {
+                1 => u8x16_eq(v, needle),
+                2 => u16x8_eq(v, needle),
+                4 => u32x4_eq(v, needle),
+                8 => i64x2_eq(v, needle),
+                _ => unreachable!(),
+            };
+            let mask = u8x16_bitmask(eq) as u32;
+            if mask != 0 {
+                let offset = 15 - mask.leading_zeros() as usize;
+                return Some(end.add(offset - offset % size));
+            }
+        }
+
+        memrchr_fallback(beg, end, val, size)
+    }
+}
+
+/// Software emulation of x86's `pmovmskb`: packs the top bit of each of the
+/// 16 bytes in `v` into a 16-bit mask. NEON has no native equivalent, so we
+/// AND in a distinct bit position per byte and pairwise-sum the halves.
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+pub(crate) unsafe fn neon_movemask(v: std::arch::aarch64::uint8x16_t) -> u32 {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        const BIT_POSITIONS: [u8; 16] = [1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128];
+        let bits = vld1q_u8(BIT_POSITIONS.as_ptr());
+        let masked = vandq_u8(v, bits);
+        let sum_lo = vaddv_u8(vget_low_u8(masked)) as u32;
+        let sum_hi = vaddv_u8(vget_high_u8(masked)) as u32;
+        sum_lo | (sum_hi << 8)
+    }
+}
+
+/// Below this many bytes, a typed element loop beats the setup cost of the
+/// vectorized path. Matches the rule of thumb used by most libc `memcpy`
+/// implementations: a couple of machine words, or 16 bytes, whichever is
+/// larger.
+#[inline(always)]
+const fn copy_threshold() -> usize {
+    let w = 2 * mem::size_of::<usize>();
+    if w > 16 { w } else { 16 }
+}
+
+/// Copies `src` into `dst`, which must be the same length and must not
+/// overlap (use [`memmove`] if they might). Clang under `-Os` emits the
+/// same trivial byte loop for typed copies that motivated this module's
+/// `memset`, so the gap buffer / rope shifting this editor does benefits
+/// from the same hand-tuned treatment.
+#[inline]
+pub fn memcopy<T: MemsetSafe>(dst: &mut [T], src: &[T]) {
+    assert_eq!(dst.len(), src.len());
+    unsafe {
+        let size = mem::size_of::<T>();
+        let len = dst.len() * size;
+        let d = dst.as_mut_ptr() as *mut u8;
+        let s = src.as_ptr() as *const u8;
+
+        if len < copy_threshold() {
+            memcopy_fallback(d, s, len);
+        } else {
+            memcopy_raw(d, s, len);
+        }
+    }
+}
+
+/// Like [`memcopy`], but `dst` and `src` are allowed to overlap.
+///
+/// Takes raw pointers rather than slices: an overlapping `&mut [T]` and
+/// `&[T]` pair would alias, which is UB regardless of what the function
+/// body does with them, so the caller must hold the non-aliasing
+/// invariants (and the `dst`/`src`-may-overlap invariant this function
+/// exists to loosen) itself.
+///
+/// # Safety
+///
+/// `dst` and `src` must each be valid for reads/writes of `len` elements
+/// of `T`. The two ranges may overlap.
+#[inline]
+pub unsafe fn memmove<T: MemsetSafe>(dst: *mut T, src: *const T, len: usize) {
+    unsafe {
+        let size = mem::size_of::<T>();
+        let byte_len = len * size;
+        let d = dst as *mut u8;
+        let s = src as *const u8;
+
+        // Forward copies are only unsafe when `dst` lands inside `src`'s
+        // range ahead of the read cursor, i.e. `src < dst < src + len`.
+        // Every other relative position -- including no overlap at all --
+        // can safely reuse the forward vectorized path... with one
+        // exception: `memcopy_raw`'s vectorized tails write their last
+        // `copy_threshold()`-ish bytes via two overlapping stores (sound
+        // only because `memcopy`'s contract forbids aliasing). If `dst`
+        // and `src` overlap with a gap smaller than that, the first store
+        // can clobber source bytes the second load still needs. Forward
+        // byte-at-a-time copying is always safe in that direction
+        // regardless of the gap, so route that narrow case through the
+        // plain fallback instead of the vectorized path.
+        if s < d && (d as *const u8) < s.add(byte_len) {
+            memmove_backward(d, s, byte_len);
+        } else if (d as *const u8) < s.add(byte_len)
+            && s < d.add(byte_len)
+            && (s as usize) - (d as usize) < copy_threshold()
+        {
+            memcopy_fallback(d, s, byte_len);
+        } else if byte_len < copy_threshold() {
+            memcopy_fallback(d, s, byte_len);
+        } else {
+            memcopy_raw(d, s, byte_len);
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn memcopy_fallback(mut dst: *mut u8, mut src: *const u8, len: usize) {
+    unsafe {
+        let end = dst.add(len);
+        while !ptr::eq(dst, end) {
+            *dst = *src;
+            dst = dst.add(1);
+            src = src.add(1);
+        }
+    }
+}
+
+#[inline(always)]
+unsafe fn memmove_backward(dst: *mut u8, src: *const u8, len: usize) {
+    unsafe {
+        let mut d = dst.add(len);
+        let mut s = src.add(len);
+        while !ptr::eq(d, dst) {
+            d = d.sub(1);
+            s = s.sub(1);
+            *d = *s;
+        }
+    }
+}
+
+#[inline]
+unsafe fn memcopy_raw(dst: *mut u8, src: *const u8, len: usize) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "loongarch64"))]
+    return unsafe { MEMCOPY_DISPATCH(dst, src, len) };
+
+    #[cfg(target_arch = "aarch64")]
+    return unsafe { memcopy_neon(dst, src, len) };
+
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    return unsafe { memcopy_simd128(dst, src, len) };
+
+    #[allow(unreachable_code)]
+    return unsafe { memcopy_fallback(dst, src, len) };
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64", target_arch = "loongarch64"))]
+static mut MEMCOPY_DISPATCH: unsafe fn(dst: *mut u8, src: *const u8, len: usize) = memcopy_dispatch;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn memcopy_dispatch(dst: *mut u8, src: *const u8, len: usize) {
+    let func = if is_x86_feature_detected!("avx2") { memcopy_avx2 } else { memcopy_sse2 };
+    unsafe { MEMCOPY_DISPATCH = func };
+    unsafe { func(dst, src, len) }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+unsafe fn memcopy_sse2(mut dst: *mut u8, mut src: *const u8, len: usize) {
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::*;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::*;
+
+        let end = dst.add(len);
+        let off = dst.align_offset(16);
+        if off != 0 && off < len {
+            memcopy_fallback(dst, src, off);
+            dst = dst.add(off);
+            src = src.add(off);
+        }
+
+        while end.offset_from_unsigned(dst) >= 32 {
+            let v1 = _mm_loadu_si128(src as *const _);
+            let v2 = _mm_loadu_si128(src.add(16) as *const _);
+            _mm_store_si128(dst as *mut _, v1);
+            _mm_store_si128(dst.add(16) as *mut _, v2);
+            dst = dst.add(32);
+            src = src.add(32);
+        }
+
+        // By overlapping the stores we can write a 16-31 byte tail with
+        // two stores, the same trick `memset_avx2` uses for its tail. A
+        // tail shorter than 16 bytes is copied by the byte loop.
+        let remaining = end.offset_from_unsigned(dst);
+        if remaining >= 16 {
+            _mm_storeu_si128(dst as *mut _, _mm_loadu_si128(src as *const _));
+            _mm_storeu_si128(
+                end.sub(16) as *mut _,
+                _mm_loadu_si128(src.add(remaining).sub(16) as *const _),
+            );
+        } else {
+            memcopy_fallback(dst, src, remaining);
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn memcopy_avx2(mut dst: *mut u8, mut src: *const u8, len: usize) {
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::*;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::*;
+
+        let end = dst.add(len);
+        let off = dst.align_offset(32);
+        if off != 0 && off < len {
+            memcopy_fallback(dst, src, off);
+            dst = dst.add(off);
+            src = src.add(off);
+        }
+
+        while end.offset_from_unsigned(dst) >= 128 {
+            let v1 = _mm256_loadu_si256(src.add(0) as *const _);
+            let v2 = _mm256_loadu_si256(src.add(32) as *const _);
+            let v3 = _mm256_loadu_si256(src.add(64) as *const _);
+            let v4 = _mm256_loadu_si256(src.add(96) as *const _);
+            _mm256_store_si256(dst.add(0) as *mut _, v1);
+            _mm256_store_si256(dst.add(32) as *mut _, v2);
+            _mm256_store_si256(dst.add(64) as *mut _, v3);
+            _mm256_store_si256(dst.add(96) as *mut _, v4);
+            dst = dst.add(128);
+            src = src.add(128);
+        }
+
+        while end.offset_from_unsigned(dst) >= 32 {
+            _mm256_store_si256(dst as *mut _, _mm256_loadu_si256(src as *const _));
+            dst = dst.add(32);
+            src = src.add(32);
+        }
+
+        let remaining = end.offset_from_unsigned(dst);
+        if remaining >= 16 {
+            _mm_storeu_si128(dst as *mut _, _mm_loadu_si128(src as *const _));
+            _mm_storeu_si128(
+                end.sub(16) as *mut _,
+                _mm_loadu_si128(src.add(remaining).sub(16) as *const _),
+            );
+        } else {
+            memcopy_fallback(dst, src, remaining);
+        }
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+fn memcopy_dispatch(dst: *mut u8, src: *const u8, len: usize) {
+    use std::arch::is_loongarch_feature_detected;
+
+    let func = if is_loongarch_feature_detected!("lasx") {
+        memcopy_lasx
+    } else if is_loongarch_feature_detected!("lsx") {
+        memcopy_lsx
+    } else {
+        memcopy_fallback
+    };
+    unsafe { MEMCOPY_DISPATCH = func };
+    unsafe { func(dst, src, len) }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lasx")]
+unsafe fn memcopy_lasx(mut dst: *mut u8, mut src: *const u8, len: usize) {
+    unsafe {
+        use std::arch::loongarch64::*;
+
+        let end = dst.add(len);
+        let off = dst.align_offset(32);
+        if off != 0 && off < len {
+            memcopy_fallback(dst, src, off);
+            dst = dst.add(off);
+            src = src.add(off);
+        }
+
+        while end.offset_from_unsigned(dst) >= 128 {
+            let v1 = lasx_xvld::<0>(src as *const _);
+            let v2 = lasx_xvld::<32>(src as *const _);
+            let v3 = lasx_xvld::<64>(src as *const _);
+            let v4 = lasx_xvld::<96>(src as *const _);
+            lasx_xvst::<0>(v1, dst as *mut _);
+            lasx_xvst::<32>(v2, dst as *mut _);
+            lasx_xvst::<64>(v3, dst as *mut _);
+            lasx_xvst::<96>(v4, dst as *mut _);
+            dst = dst.add(128);
+            src = src.add(128);
+        }
+
+        while end.offset_from_unsigned(dst) >= 32 {
+            lasx_xvst::<0>(lasx_xvld::<0>(src as *const _), dst as *mut _);
+            dst = dst.add(32);
+            src = src.add(32);
+        }
+
+        memcopy_fallback(dst, src, end.offset_from_unsigned(dst))
+    }
+}
+
+#[cfg(target_arch = "loongarch64")]
+#[target_feature(enable = "lsx")]
+unsafe fn memcopy_lsx(mut dst: *mut u8, mut src: *const u8, len: usize) {
+    unsafe {
+        use std::arch::loongarch64::*;
+
+        let end = dst.add(len);
+        let off = dst.align_offset(16);
+        if off != 0 && off < len {
+            memcopy_fallback(dst, src, off);
+            dst = dst.add(off);
+            src = src.add(off);
+        }
+
+        while end.offset_from_unsigned(dst) >= 32 {
+            let v1 = lsx_vld::<0>(src as *const _);
+            let v2 = lsx_vld::<16>(src as *const _);
+            lsx_vst::<0>(v1, dst as *mut _);
+            lsx_vst::<16>(v2, dst as *mut _);
+            dst = dst.add(32);
+            src = src.add(32);
+        }
+
+        let remaining = end.offset_from_unsigned(dst);
+        if remaining >= 16 {
+            lsx_vst::<0>(lsx_vld::<0>(src as *const _), dst as *mut _);
+            lsx_vst::<-16>(lsx_vld::<-16>(src.add(remaining) as *const _), end as *mut _);
+        } else {
+            memcopy_fallback(dst, src, remaining);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn memcopy_neon(mut dst: *mut u8, mut src: *const u8, len: usize) {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        let end = dst.add(len);
+        let off = dst.align_offset(16);
+        if off != 0 && off < len {
+            memcopy_fallback(dst, src, off);
+            dst = dst.add(off);
+            src = src.add(off);
+        }
+
+        while end.offset_from_unsigned(dst) >= 32 {
+            let v1 = vld1q_u8(src);
+            let v2 = vld1q_u8(src.add(16));
+            vst1q_u8(dst, v1);
+            vst1q_u8(dst.add(16), v2);
+            dst = dst.add(32);
+            src = src.add(32);
+        }
+
+        let remaining = end.offset_from_unsigned(dst);
+        if remaining >= 16 {
+            vst1q_u8(dst, vld1q_u8(src));
+            vst1q_u8(end.sub(16), vld1q_u8(src.add(remaining).sub(16)));
+        } else {
+            memcopy_fallback(dst, src, remaining);
+        }
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+unsafe fn memcopy_simd128(mut dst: *mut u8, mut src: *const u8, len: usize) {
+    unsafe {
+        use std::arch::wasm32::*;
+
+        let end = dst.add(len);
+        let off = dst.align_offset(16);
+        if off != 0 && off < len {
+            memcopy_fallback(dst, src, off);
+            dst = dst.add(off);
+            src = src.add(off);
+        }
+
+        while end.offset_from_unsigned(dst) >= 32 {
+            let v1 = v128_load(src as *const _);
+            let v2 = v128_load(src.add(16) as *const _);
+            v128_store(dst as *mut _, v1);
+            v128_store(dst.add(16) as *mut _, v2);
+            dst = dst.add(32);
+            src = src.add(32);
+        }
+
+        let remaining = end.offset_from_unsigned(dst);
+        if remaining >= 16 {
+            v128_store(dst as *mut _, v128_load(src as *const _));
+            v128_store(end.sub(16) as *mut _, v128_load(src.add(remaining).sub(16) as *const _));
+        } else {
+            memcopy_fallback(dst, src, remaining);
+        }
+    }
+}
+
+/// Returns whether `a` and `b` hold the same elements.
+#[inline]
+pub fn memeq<T: MemsetSafe + PartialEq>(a: &[T], b: &[T]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let len = a.len() * mem::size_of::<T>();
+    diff_offset_raw(a.as_ptr() as *const u8, b.as_ptr() as *const u8, len).is_none()
+}
+
+/// Lexicographically compares `a` and `b` element by element.
+///
+/// The search for *where* the two slices first differ is done as a fast
+/// byte-oriented block scan, but the actual ordering of that element is
+/// decided by `T`'s own `Ord` impl, so this is correct regardless of the
+/// element type's in-memory (e.g. little-endian) byte order.
+#[inline]
+pub fn memcmp<T: MemsetSafe + Ord>(a: &[T], b: &[T]) -> Ordering {
+    let common = a.len().min(b.len());
+    let byte_len = common * mem::size_of::<T>();
+
+    let diff = diff_offset_raw(a.as_ptr() as *const u8, b.as_ptr() as *const u8, byte_len);
+
+    match diff {
+        Some(offset) => {
+            let index = offset / mem::size_of::<T>();
+            a[index].cmp(&b[index])
+        }
+        None => a.len().cmp(&b.len()),
+    }
+}
+
+/// Finds the byte offset of the first position where `a` and `b` differ,
+/// or `None` if the first `len` bytes of both are equal.
+#[inline]
+fn diff_offset_raw(a: *const u8, b: *const u8, len: usize) -> Option<usize> {
+    unsafe {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        return DIFF_DISPATCH(a, b, len);
+
+        #[cfg(target_arch = "aarch64")]
+        return diff_offset_neon(a, b, len);
+
+        #[allow(unreachable_code)]
+        return diff_offset_fallback(a, b, len);
+    }
+}
+
+/// Word-wise scalar fallback: XOR each `u64` word and take the lowest set
+/// bit of the result to find the first differing byte, then a plain byte
+/// loop for the 0-7 byte tail.
+#[inline(never)]
+unsafe fn diff_offset_fallback(mut a: *const u8, mut b: *const u8, len: usize) -> Option<usize> {
+    unsafe {
+        let beg = a;
+        let mut remaining = len as isize;
+
+        while remaining >= 8 {
+            let wa = (a as *const u64).read_unaligned();
+            let wb = (b as *const u64).read_unaligned();
+            let x = wa ^ wb;
+            if x != 0 {
+                return Some(a.offset_from_unsigned(beg) + (x.trailing_zeros() / 8) as usize);
+            }
+            a = a.add(8);
+            b = b.add(8);
+            remaining -= 8;
+        }
+
+        while remaining > 0 {
+            if *a != *b {
+                return Some(a.offset_from_unsigned(beg));
+            }
+            a = a.add(1);
+            b = b.add(1);
+            remaining -= 1;
+        }
+
+        None
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+static mut DIFF_DISPATCH: unsafe fn(a: *const u8, b: *const u8, len: usize) -> Option<usize> =
+    diff_offset_dispatch;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+unsafe fn diff_offset_dispatch(a: *const u8, b: *const u8, len: usize) -> Option<usize> {
+    let func = if is_x86_feature_detected!("avx2") { diff_offset_avx2 } else { diff_offset_sse2 };
+    unsafe { DIFF_DISPATCH = func };
+    unsafe { func(a, b, len) }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+unsafe fn diff_offset_sse2(mut a: *const u8, mut b: *const u8, len: usize) -> Option<usize> {
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::*;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::*;
+
+        let beg = a;
+        let end = a.add(len);
+
+        while end.offset_from_unsigned(a) >= 16 {
+            let va = _mm_loadu_si128(a as *const _);
+            let vb = _mm_loadu_si128(b as *const _);
+            let eq = _mm_cmpeq_epi8(va, vb);
+            let mask = _mm_movemask_epi8(eq) as u16;
+            if mask != 0xFFFF {
+                let offset = (!mask).trailing_zeros() as usize;
+                return Some(a.offset_from_unsigned(beg) + offset);
+            }
+            a = a.add(16);
+            b = b.add(16);
+        }
+
+        // Overlapping tail: any byte re-checked here was already proven
+        // equal by the loop above, so the first real mismatch -- if any --
+        // still comes out at the right absolute offset.
+        let remaining = end.offset_from_unsigned(a);
+        if remaining > 0 {
+            return diff_offset_tail_sse2(a, b, beg, end);
+        }
+
+        None
+    }
+}
+
+/// Handles the 1-15 byte remainder of [`diff_offset_sse2`]. If the whole
+/// buffer is at least 16 bytes long, this loads one final window aligned to
+/// `end` -- overlapping whatever the main loop already checked -- instead
+/// of a byte loop. Bytes re-covered by the overlap were already proven
+/// equal, so any mismatch this window finds is still the true first one.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+unsafe fn diff_offset_tail_sse2(a: *const u8, b: *const u8, beg: *const u8, end: *const u8) -> Option<usize> {
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::*;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::*;
+
+        if end.offset_from_unsigned(beg) >= 16 {
+            let a2 = end.sub(16);
+            let b2 = b.sub(a.offset_from_unsigned(a2));
+            let va = _mm_loadu_si128(a2 as *const _);
+            let vb = _mm_loadu_si128(b2 as *const _);
+            let eq = _mm_cmpeq_epi8(va, vb);
+            let mask = _mm_movemask_epi8(eq) as u16;
+            if mask != 0xFFFF {
+                let offset = (!mask).trailing_zeros() as usize;
+                return Some(a2.add(offset).offset_from_unsigned(beg));
+            }
+            return None;
+        }
+
+        let remaining = end.offset_from_unsigned(a) as usize;
+        diff_offset_fallback(a, b, remaining).map(|o| o + a.offset_from_unsigned(beg))
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn diff_offset_avx2(mut a: *const u8, mut b: *const u8, len: usize) -> Option<usize> {
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use std::arch::x86::*;
+        #[cfg(target_arch = "x86_64")]
+        use std::arch::x86_64::*;
+
+        let beg = a;
+        let end = a.add(len);
+
+        while end.offset_from_unsigned(a) >= 32 {
+            let va = _mm256_loadu_si256(a as *const _);
+            let vb = _mm256_loadu_si256(b as *const _);
+            let x = _mm256_xor_si256(va, vb);
+            if _mm256_testz_si256(x, x) == 0 {
+                // Found a differing 32-byte block: fall back to a
+                // byte-precise scan over just this block to pin down the
+                // exact offset.
+                return diff_offset_fallback(a, b, 32).map(|o| o + a.offset_from_unsigned(beg));
+            }
+            a = a.add(32);
+            b = b.add(32);
+        }
+
+        let remaining = end.offset_from_unsigned(a);
+        if remaining >= 16 {
+            return diff_offset_sse2(a, b, remaining as usize).map(|o| o + a.offset_from_unsigned(beg));
+        }
+        if remaining > 0 {
+            return diff_offset_fallback(a, b, remaining as usize).map(|o| o + a.offset_from_unsigned(beg));
+        }
+
+        None
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn diff_offset_neon(mut a: *const u8, mut b: *const u8, len: usize) -> Option<usize> {
+    unsafe {
+        use std::arch::aarch64::*;
+
+        let beg = a;
+        let end = a.add(len);
+
+        while end.offset_from_unsigned(a) >= 16 {
+            let va = vld1q_u8(a);
+            let vb = vld1q_u8(b);
+            let eq = vceqq_u8(va, vb);
+            let mask = neon_movemask(eq);
+            if mask != 0xFFFF {
+                let offset = (!mask & 0xFFFF).trailing_zeros() as usize;
+                return Some(a.offset_from_unsigned(beg) + offset);
+            }
+            a = a.add(16);
+            b = b.add(16);
+        }
+
+        let remaining = end.offset_from_unsigned(a);
+        if remaining > 0 {
+            return diff_offset_fallback(a, b, remaining as usize).map(|o| o + a.offset_from_unsigned(beg));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::ops::Not;
+
+    use super::*;
+
+    fn check_memset<T>(val: T, len: usize)
+    where
+        T: MemsetSafe + Not<Output = T> + PartialEq + fmt::Debug,
+    {
+        let mut buf = vec![!val; len];
+        memset(&mut buf, val);
+        assert!(buf.iter().all(|&x| x == val));
+    }
+
+    #[test]
+    fn test_memset_empty() {
+        check_memset(0u8, 0);
+        check_memset(0u16, 0);
+        check_memset(0u32, 0);
+        check_memset(0u64, 0);
+    }
+
+    #[test]
+    fn test_memset_single() {
+        check_memset(0u8, 1);
+        check_memset(0xFFu8, 1);
+        check_memset(0xABu16, 1);
+        check_memset(0x12345678u32, 1);
+        check_memset(0xDEADBEEFu64, 1);
+    }
+
+    #[test]
+    fn test_memset_small() {
+        for &len in &[2, 3, 4, 5, 7, 8, 9] {
+            check_memset(0xAAu8, len);
+            check_memset(0xBEEFu16, len);
+            check_memset(0xCAFEBABEu32, len);
+            check_memset(0x1234567890ABCDEFu64, len);
+        }
+    }
+
+    #[test]
+    fn test_memset_large() {
+        check_memset(0u8, 1000);
+        check_memset(0xFFu8, 1024);
+        check_memset(0xBEEFu16, 512);
+        check_memset(0xCAFEBABEu32, 256);
+        check_memset(0x1234567890ABCDEFu64, 128);
+    }
+
+    #[test]
+    fn test_memset_various_values() {
+        check_memset(0u8, 17);
+        check_memset(0x7Fu8, 17);
+        check_memset(0x8001u16, 17);
+        check_memset(0xFFFFFFFFu32, 17);
+        check_memset(0x8000000000000001u64, 17);
+    }
+
+    #[test]
+    fn test_memset_signed_types() {
+        check_memset(-1i8, 8);
+        check_memset(-2i16, 8);
+        check_memset(-3i32, 8);
+        check_memset(-4i64, 8);
+        check_memset(-5isize, 8);
+    }
+
+    #[test]
+    fn test_memset_usize_isize() {
+        check_memset(0usize, 4);
+        check_memset(usize::MAX, 4);
+        check_memset(0isize, 4);
+        check_memset(isize::MIN, 4);
+    }
+
+    #[test]
+    fn test_memset_alignment() {
+        // Check that memset works for slices not aligned to 8 bytes
+        let mut buf = [0u8; 15];
+        for offset in 0..8 {
+            let slice = &mut buf[offset..(offset + 7)];
+            memset(slice, 0x5A);
+            assert!(slice.iter().all(|&x| x == 0x5A));
+        }
+    }
+
+    #[test]
+    fn test_memset_array() {
+        let mut a = [0u8; 4];
+        memset_array(&mut a, 0xAB);
+        assert_eq!(a, [0xAB; 4]);
+
+        let mut b = [0u16; 3];
+        memset_array(&mut b, 0xBEEF);
+        assert_eq!(b, [0xBEEF; 3]);
+
+        let mut c = [0u32; 1];
+        memset_array(&mut c, 0xCAFEBABE);
+        assert_eq!(c, [0xCAFEBABE; 1]);
+
+        // Exercises the `memset` delegation path for arrays above 16 bytes.
+        let mut d = [0u64; 8];
+        memset_array(&mut d, 0x1234567890ABCDEF);
+        assert_eq!(d, [0x1234567890ABCDEF; 8]);
+    }
+
+    #[test]
+    fn test_memchr_u8() {
+        let buf: Vec<u8> = (0..200).map(|i| (i % 7) as u8).collect();
+        assert_eq!(memchr(&buf, 0), Some(0));
+        assert_eq!(memchr(&buf, 6), Some(6));
+        assert_eq!(memchr(&buf, 255), None);
+        assert_eq!(memchr(&[] as &[u8], 1), None);
+    }
+
+    #[test]
+    fn test_memrchr_u8() {
+        let buf: Vec<u8> = (0..200).map(|i| (i % 7) as u8).collect();
+        assert_eq!(memrchr(&buf, 0), Some(196));
+        assert_eq!(memrchr(&buf, 6), Some(195));
+        assert_eq!(memrchr(&buf, 255), None);
+    }
+
+    #[test]
+    fn test_memchr_wide_types() {
+        let buf16: Vec<u16> = (0..100).collect();
+        assert_eq!(memchr(&buf16, 42), Some(42));
+        assert_eq!(memrchr(&buf16, 42), Some(42));
+
+        let buf32: Vec<u32> = (0..100).collect();
+        assert_eq!(memchr(&buf32, 99), Some(99));
+
+        let buf64: Vec<u64> = (0..100).collect();
+        assert_eq!(memchr(&buf64, 0), Some(0));
+        assert_eq!(memrchr(&buf64, 50), Some(50));
+    }
+
+    #[test]
+    fn test_memchr_unaligned() {
+        let buf: Vec<u8> = (0..64).collect();
+        for offset in 1..8 {
+            let slice = &buf[offset..];
+            assert_eq!(memchr(slice, buf[offset + 3]), Some(3));
+        }
+    }
+
+    #[test]
+    fn test_memcopy() {
+        for len in [0, 1, 7, 15, 16, 31, 32, 100, 1000] {
+            let src: Vec<u8> = (0..len as u32).map(|i| i as u8).collect();
+            let mut dst = vec![0u8; len];
+            memcopy(&mut dst, &src);
+            assert_eq!(dst, src);
+        }
+
+        let src: Vec<u32> = (0..64).collect();
+        let mut dst = vec![0u32; 64];
+        memcopy(&mut dst, &src);
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn test_memmove_overlap_forward() {
+        // dst > src: the backward copy path.
+        let mut buf: Vec<u8> = (0..100).collect();
+        let expected: Vec<u8> = buf[0..90].to_vec();
+        unsafe {
+            let src = buf.as_ptr();
+            let dst = buf.as_mut_ptr().add(10);
+            memmove(dst, src, 90);
+        }
+        assert_eq!(&buf[10..100], &expected[..]);
+    }
+
+    #[test]
+    fn test_memmove_overlap_backward() {
+        // dst < src: safe to reuse the forward copy path.
+        let mut buf: Vec<u8> = (0..100).collect();
+        let expected: Vec<u8> = buf[10..100].to_vec();
+        unsafe {
+            let src = buf.as_ptr().add(10);
+            let dst = buf.as_mut_ptr();
+            memmove(dst, src, 90);
+        }
+        assert_eq!(&buf[0..90], &expected[..]);
+    }
+
+    #[test]
+    fn test_memeq() {
+        let a: Vec<u8> = (0..200).collect();
+        let b = a.clone();
+        assert!(memeq(&a, &b));
+
+        for i in 0..a.len() {
+            let mut c = a.clone();
+            c[i] ^= 0xFF;
+            assert!(!memeq(&a, &c), "differing at {i} should be detected");
+        }
+
+        assert!(!memeq(&a, &a[..a.len() - 1]));
+    }
+
+    #[test]
+    fn test_memcmp() {
+        assert_eq!(memcmp(&[1u8, 2, 3], &[1u8, 2, 3]), Ordering::Equal);
+        assert_eq!(memcmp(&[1u8, 2, 3], &[1u8, 2, 4]), Ordering::Less);
+        assert_eq!(memcmp(&[1u8, 2, 4], &[1u8, 2, 3]), Ordering::Greater);
+        assert_eq!(memcmp(&[1u8, 2], &[1u8, 2, 3]), Ordering::Less);
+        assert_eq!(memcmp(&[1u8, 2, 3], &[1u8, 2]), Ordering::Greater);
+
+        // The differing byte lands past a 32-byte AVX2 block boundary.
+        let mut a = vec![0u8; 64];
+        let mut b = vec![0u8; 64];
+        a[40] = 1;
+        assert_eq!(memcmp(&a, &b), Ordering::Greater);
+        b[40] = 2;
+        assert_eq!(memcmp(&a, &b), Ordering::Less);
+
+        let a: Vec<u32> = vec![1, 2, 3];
+        let b: Vec<u32> = vec![1, 2, 4];
+        assert_eq!(memcmp(&a, &b), Ordering::Less);
     }
 }